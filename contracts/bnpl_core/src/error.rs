@@ -12,6 +12,7 @@ pub enum Error {
     NotAdmin = 11,
     AdminAlreadySet = 12,
     CannotRemoveItself = 13,
+    NotArbiter = 14,
     
     // Bill errors
     BillNotFound = 20,
@@ -20,6 +21,16 @@ pub enum Error {
     BillExpired = 23,
     LiquidationNotPossible = 24,
     InvalidAmount = 25,
+    DuplicateOrder = 26,
+    DisputeNotOpen = 27,
+    InvalidTerm = 28,
+    OfferExpired = 29,
+    NonceUsed = 30,
+    InvalidSignature = 31,
+    AutoRepayDisabled = 32,
+    BillNotDue = 33,
+    InsufficientFunds = 34,
+    OfferNotForUser = 35,
     
     // Liquidation errors
     InvalidInstallmentNumber = 41,
@@ -30,9 +41,21 @@ pub enum Error {
     // General errors
     InvalidInput = 100,
     InternalError = 101,
+    InvalidFeeSplit = 102,
     
     // Merchant errors
     MerchantAlreadyEnrolled = 110,
     MerchantNotFound = 111,
     MerchantNotApproved = 112,
+    NotBillCreator = 113,
+    TooManyBillCreators = 114,
+    BillCreatorTaken = 115,
+    InvalidStatusTransition = 116,
+    InsufficientBond = 117,
+    BondLocked = 118,
+    ExposureLimitRequired = 119,
+    
+    // Pool errors
+    PoolAlreadyRegistered = 120,
+    PoolNotFound = 121,
 }
\ No newline at end of file
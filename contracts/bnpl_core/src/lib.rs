@@ -3,16 +3,17 @@
 mod storage;
 mod types;
 mod error;
+mod oracle;
 
 #[cfg(test)]
 mod test;
 
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec, Map, symbol_short};
+use soroban_sdk::{contract, contractimpl, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec, Map, symbol_short};
 
 pub use crate::types::*;
 pub use crate::error::Error;
-use crate::storage::DataKey;
+pub use crate::oracle::{PriceData, PriceOracleClient, PriceOracleInterface};
 use lp_token_interface::LPTokenClient;
 
 // === FEE CONSTANTS ===
@@ -25,6 +26,7 @@ const LATE_INTEREST_APR: i128 = 3_000_000; // 30% APR (scaled by 10^7)
 const LIQUIDATION_PENALTY: i128 = 100_000; // 1% (scaled by 10^7)
 
 // Fee distribution ratios (total must equal 100%)
+// Default fee split, overridable per fee type
 const FEE_TO_LP_RATIO: i128 = 7_000_000;      // 70% to LPs
 const FEE_TO_TREASURY_RATIO: i128 = 2_000_000; // 20% to Treasury
 const FEE_TO_INSURANCE_RATIO: i128 = 1_000_000; // 10% to Insurance Fund
@@ -38,6 +40,30 @@ const BILL_DURATION_DAYS: u64 = 1; // 1 day for bill expiration
 const GRACE_PERIOD_DAYS: u64 = 14; // 14 days grace period before late fee
 const LIQUIDATION_THRESHOLD_DAYS: u64 = 28; // 28 days grace period before liquidation
 const SECONDS_PER_DAY: u64 = 86400; // 60 * 60 * 24
+const LATE_FEE_DENOMINATOR: i128 = 365 * SECONDS_PER_DAY as i128 * SCALE_7; // Late APR accrues per second
+
+// Auto-repay
+const KEEPER_TIP_RATE: i128 = 10_000; // 0.1% of principal paid by the user to the keeper (scaled by 10^7)
+const AUTO_REPAY_WINDOW_DAYS: u64 = 1; // Days before the grace period ends that keepers may repay
+
+// Merchant bond
+const MERCHANT_BOND_RATIO: i128 = 1_000_000; // Bond of 10% of the exposure limit (scaled by 10^7)
+const BOND_COOLDOWN_DAYS: u64 = 30; // Days after cancellation before the bond can be withdrawn
+
+// Merchant key management
+const PAYOUT_CHANGE_DELAY_DAYS: u64 = 2; // Delay before a new payout address takes effect
+const MAX_BILL_CREATORS: u32 = 10;
+
+// Bill metadata limits
+const MAX_CURRENCY_CODE_LEN: u32 = 12;
+const MAX_MEMO_LEN: u32 = 128;
+
+// User bill index limits
+const MAX_OPEN_BILLS: u32 = 50; // Open bills per user
+const MAX_PAGE_SIZE: u32 = 50; // Bills per page in paginated views
+const MAX_MERCHANT_TERMS: u32 = 8; // Promotional terms per merchant
+const MAX_BATCH_SIZE: u32 = 25; // Items per batch call
+const MAX_FEE_SPLITS: u32 = 10; // Recipients per fee type
 
 #[contract]
 pub struct UnifiedBNPLContract;
@@ -67,6 +93,10 @@ impl UnifiedBNPLContract {
 
         storage::set_config(&env, &config);
         
+        // Register the default pool
+        storage::set_pool(&env, &config.usdc_token, &config.liquidity_pool);
+        storage::set_pool_assets(&env, &Vec::from_array(&env, [config.usdc_token.clone()]));
+        
         // Initialize counters
         storage::set_bill_counter(&env, 1);
 
@@ -81,32 +111,212 @@ impl UnifiedBNPLContract {
         storage::get_config(&env)
     }
     
-    // internal function to distribute fees to treasury, insurance fund, and LPs
-    fn distribute_fees(env: Env, amount: i128) {
-        let config = storage::get_config(&env);
-        let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
+    // === POOL MANAGEMENT ===
+
+    /// Register the liquidity pool for a stablecoin asset (admin only)
+    pub fn register_pool(
+        env: Env,
+        admin: Address,
+        asset: Address,
+        pool: Address,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin.clone()) {
+            return Err(Error::NotAdmin);
+        }
+        
+        if storage::get_pool(&env, &asset).is_some() {
+            return Err(Error::PoolAlreadyRegistered);
+        }
+        
+        storage::set_pool(&env, &asset, &pool);
+        let mut assets = storage::get_pool_assets(&env);
+        assets.push_back(asset.clone());
+        storage::set_pool_assets(&env, &assets);
+        
+        env.events().publish(
+            (symbol_short!("pool_add"), asset.clone()),
+            PoolRegisteredEvent {
+                asset,
+                pool,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Get the liquidity pool registered for an asset
+    pub fn get_pool(env: Env, asset: Address) -> Option<Address> {
+        storage::get_pool(&env, &asset)
+    }
+    
+    /// Get all assets with a registered pool
+    pub fn get_pool_assets(env: Env) -> Vec<Address> {
+        storage::get_pool_assets(&env)
+    }
+    
+    // internal function to resolve the pool for an asset
+    fn pool_for(env: &Env, asset: &Address) -> Address {
+        match storage::get_pool(env, asset) {
+            Some(pool) => pool,
+            None => panic!("Pool not found"),
+        }
+    }
+    
+    // internal function to accrue a fee to the recipients of its fee type's split
+    // Shares truncate and the remainder goes to the first split, so no dust is left unassigned
+    fn distribute_fees(env: Env, asset: Address, fee_type: FeeType, amount: i128) {
+        if amount <= 0 {
+            return;
+        }
+        
+        let splits = Self::get_fee_splits(env.clone(), fee_type.clone());
+        let mut assigned = 0;
+        for (_, ratio) in splits.iter() {
+            assigned += amount * ratio / SCALE_7;
+        }
+        
+        for (i, (recipient, ratio)) in splits.iter().enumerate() {
+            let mut share = amount * ratio / SCALE_7;
+            if i == 0 {
+                share += amount - assigned;
+            }
+            if share <= 0 {
+                continue;
+            }
+            
+            if recipient == env.current_contract_address() {
+                storage::set_pending_lp_fees(&env, &asset, storage::get_pending_lp_fees(&env, &asset) + share);
+            } else {
+                storage::add_accrued_fees(&env, &recipient, &asset, share);
+            }
+            
+            env.events().publish(
+                (symbol_short!("fee_split"), recipient.clone()),
+                FeeSplitEvent {
+                    fee_type: fee_type.clone(),
+                    recipient,
+                    asset: asset.clone(),
+                    amount: share,
+                }
+            );
+        }
+    }
+
+    // === FEE SPLITS ===
+
+    /// Set the recipients of a fee type as (recipient, ratio) pairs summing to SCALE_7 (admin only)
+    /// This contract's own address stands for the LPs of the pool the fee is paid in
+    pub fn set_fee_splits(
+        env: Env,
+        admin: Address,
+        fee_type: FeeType,
+        splits: Vec<(Address, i128)>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        if splits.is_empty() || splits.len() > MAX_FEE_SPLITS {
+            return Err(Error::InvalidFeeSplit);
+        }
+        let mut total = 0;
+        for (_, ratio) in splits.iter() {
+            if ratio <= 0 {
+                return Err(Error::InvalidFeeSplit);
+            }
+            total += ratio;
+        }
+        if total != SCALE_7 {
+            return Err(Error::InvalidFeeSplit);
+        }
         
-        let treasury_amount = amount * FEE_TO_TREASURY_RATIO / SCALE_7;
-        let insurance_amount = amount * FEE_TO_INSURANCE_RATIO / SCALE_7;
-        let lp_amount = amount * FEE_TO_LP_RATIO / SCALE_7;
+        storage::set_fee_splits(&env, &fee_type, &splits);
+        Ok(())
+    }
     
-        // Transfer the fees to the treasury and insurance fund
-        if treasury_amount > 0 {    
-            usdc_client.transfer(&env.current_contract_address(), &config.treasury, &treasury_amount);
+    /// Recipients of a fee type; defaults to LPs, treasury and insurance fund
+    pub fn get_fee_splits(env: Env, fee_type: FeeType) -> Vec<(Address, i128)> {
+        match storage::get_fee_splits(&env, &fee_type) {
+            Some(splits) => splits,
+            None => {
+                let config = storage::get_config(&env);
+                Vec::from_array(&env, [
+                    (env.current_contract_address(), FEE_TO_LP_RATIO),
+                    (config.treasury, FEE_TO_TREASURY_RATIO),
+                    (config.insurance_fund, FEE_TO_INSURANCE_RATIO),
+                ])
+            }
         }
-        if insurance_amount > 0 {
-            usdc_client.transfer(&env.current_contract_address(), &config.insurance_fund, &insurance_amount);
+    }
+
+    // === FEE PAYOUTS ===
+
+    /// Push all pending LP fees into their pools as yield (permissionless)
+    /// Each pool receives one `distribute_yield` call, and with it one index update
+    pub fn flush_fees(env: Env) {
+        for asset in storage::get_pool_assets(&env).iter() {
+            let amount = storage::get_pending_lp_fees(&env, &asset);
+            if amount <= 0 {
+                continue;
+            }
+            storage::set_pending_lp_fees(&env, &asset, 0);
+            
+            let liquidity_pool = Self::pool_for(&env, &asset);
+            let usdc_client = soroban_sdk::token::Client::new(&env, &asset);
+            usdc_client.approve(&env.current_contract_address(), &liquidity_pool, &amount, &env.ledger().sequence());
+            LPTokenClient::new(&env, &liquidity_pool).distribute_yield(&env.current_contract_address(), &amount);
+            
+            env.events().publish(
+                (symbol_short!("fee_flush"), asset.clone()),
+                FeesFlushedEvent {
+                    asset,
+                    amount,
+                }
+            );
         }
+    }
+    
+    /// Pay out everything accrued to a recipient, e.g. the treasury or insurance fund
+    /// Returns the amount claimed per asset
+    pub fn claim_fees(env: Env, recipient: Address) -> Map<Address, i128> {
+        recipient.require_auth();
         
-        // Distribute LP yield by transferring to LP token contract and notifying it
-        if lp_amount > 0 {
-            // Transfer the yield to LP token contract
-            usdc_client.transfer(&env.current_contract_address(), &config.liquidity_pool, &lp_amount);
+        let mut claimed = Map::new(&env);
+        for asset in storage::get_pool_assets(&env).iter() {
+            let amount = storage::get_accrued_fees(&env, &recipient, &asset);
+            if amount <= 0 {
+                continue;
+            }
+            storage::set_accrued_fees(&env, &recipient, &asset, 0);
             
-            // Notify LP token contract to update its yield tracking
-            let lp_client = LPTokenClient::new(&env, &config.liquidity_pool);
-            lp_client.update_index();
+            let usdc_client = soroban_sdk::token::Client::new(&env, &asset);
+            usdc_client.transfer(&env.current_contract_address(), &recipient, &amount);
+            claimed.set(asset.clone(), amount);
+            
+            env.events().publish(
+                (symbol_short!("fee_claim"), recipient.clone()),
+                FeesClaimedEvent {
+                    recipient: recipient.clone(),
+                    asset,
+                    amount,
+                }
+            );
         }
+        
+        claimed
+    }
+    
+    /// Fees accrued to a recipient in an asset and not yet claimed
+    pub fn get_accrued_fees(env: Env, recipient: Address, asset: Address) -> i128 {
+        storage::get_accrued_fees(&env, &recipient, &asset)
+    }
+    
+    /// LP fees of an asset's pool waiting for the next flush
+    pub fn get_pending_lp_fees(env: Env, asset: Address) -> i128 {
+        storage::get_pending_lp_fees(&env, &asset)
     }
 
     pub fn is_admin(env: Env, address: Address) -> bool {
@@ -125,8 +335,6 @@ impl UnifiedBNPLContract {
         merchant.require_auth();
         
         // Check if merchant is already enrolled
-        let key = DataKey::MerchantData(merchant.clone());
-        
         if storage::get_merchant_data(&env, &merchant).is_some() {
             return Err(Error::MerchantAlreadyEnrolled);
         }
@@ -134,6 +342,12 @@ impl UnifiedBNPLContract {
         let merchant_data = MerchantData {
             merchant_info_id: merchant_info_id.clone(),
             status: MerchantStatus::Pending,
+            payout_address: None,
+            pending_payout: None,
+            payout_effective_at: 0,
+            bill_creators: Vec::new(&env),
+            status_reason: 0,
+            status_updated_at: env.ledger().timestamp(),
         };
         
         // Store merchant data
@@ -153,11 +367,13 @@ impl UnifiedBNPLContract {
     }
     
     /// Update merchant status (admin only)
+    /// Only moves allowed by `is_valid_transition` are accepted; `reason` is an off-chain reason code
     pub fn update_merchant_status(
         env: Env,
         admin: Address,
         merchant: Address,
         new_status: MerchantStatus,
+        reason: u32,
     ) -> Result<(), Error> {
         admin.require_auth();
         
@@ -173,8 +389,23 @@ impl UnifiedBNPLContract {
         }
         
         let old_status = merchant_data.clone().unwrap().status.clone();
+        if !Self::is_valid_transition(old_status.clone(), new_status.clone()) {
+            return Err(Error::InvalidStatusTransition);
+        }
+        // Approval needs an exposure limit so there is always a bond backing the merchant
+        if new_status == MerchantStatus::Approved {
+            if storage::get_merchant_exposure_limit(&env, &merchant).is_none() {
+                return Err(Error::ExposureLimitRequired);
+            }
+            if !Self::is_bond_sufficient(&env, &merchant) {
+                return Err(Error::InsufficientBond);
+            }
+        }
+        
         let mut new_merchant_data = merchant_data.clone().unwrap();
         new_merchant_data.status = new_status.clone();
+        new_merchant_data.status_reason = reason;
+        new_merchant_data.status_updated_at = env.ledger().timestamp();
         
         // Update storage
         storage::set_merchant_data(&env, &merchant, &new_merchant_data);
@@ -186,6 +417,42 @@ impl UnifiedBNPLContract {
                 merchant: merchant.clone(),
                 old_status, 
                 new_status: new_status.clone(),
+                reason,
+                timestamp: env.ledger().timestamp(),
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Re-apply after a rejection, moving the merchant back to pending review
+    pub fn reapply_merchant(
+        env: Env,
+        merchant: Address,
+        merchant_info_id: String,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        let mut merchant_data = match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => data,
+            None => return Err(Error::MerchantNotFound),
+        };
+        
+        if merchant_data.status != MerchantStatus::Rejected {
+            return Err(Error::InvalidStatusTransition);
+        }
+        
+        merchant_data.merchant_info_id = merchant_info_id.clone();
+        merchant_data.status = MerchantStatus::Pending;
+        merchant_data.status_reason = 0;
+        merchant_data.status_updated_at = env.ledger().timestamp();
+        storage::set_merchant_data(&env, &merchant, &merchant_data);
+        
+        env.events().publish(
+            (symbol_short!("m_reapply"), merchant.clone()),
+            MerchantEnrolledEvent {
+                merchant,
+                merchant_info_id,
                 timestamp: env.ledger().timestamp(),
             }
         );
@@ -193,6 +460,20 @@ impl UnifiedBNPLContract {
         Ok(())
     }
     
+    /// Merchant status transitions the admin may perform
+    /// Rejected -> Pending only happens through `reapply_merchant`; Cancelled is final
+    pub fn is_valid_transition(from: MerchantStatus, to: MerchantStatus) -> bool {
+        matches!(
+            (from, to),
+            (MerchantStatus::Pending, MerchantStatus::Approved)
+                | (MerchantStatus::Pending, MerchantStatus::Rejected)
+                | (MerchantStatus::Approved, MerchantStatus::Suspended)
+                | (MerchantStatus::Approved, MerchantStatus::Cancelled)
+                | (MerchantStatus::Suspended, MerchantStatus::Approved)
+                | (MerchantStatus::Suspended, MerchantStatus::Cancelled)
+        )
+    }
+    
     /// Get merchant data by address
     pub fn get_merchant(
         env: Env,
@@ -201,24 +482,220 @@ impl UnifiedBNPLContract {
         storage::get_merchant_data(&env, &merchant).unwrap()
     }
 
+    /// Request a new payout address; it takes effect after `PAYOUT_CHANGE_DELAY_DAYS`
+    pub fn request_payout_address(
+        env: Env,
+        merchant: Address,
+        payout_address: Address,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        let mut merchant_data = match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => data,
+            None => return Err(Error::MerchantNotFound),
+        };
+        
+        // Apply a change whose delay already passed before queueing the next one
+        merchant_data.payout_address = Some(Self::resolve_payout(&env, &merchant, &merchant_data));
+        merchant_data.pending_payout = Some(payout_address.clone());
+        merchant_data.payout_effective_at = env.ledger().timestamp() + PAYOUT_CHANGE_DELAY_DAYS * SECONDS_PER_DAY;
+        storage::set_merchant_data(&env, &merchant, &merchant_data);
+        
+        env.events().publish(
+            (symbol_short!("payout_rq"), merchant.clone()),
+            PayoutAddressRequestedEvent {
+                merchant,
+                payout_address,
+                effective_at: merchant_data.payout_effective_at,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Cancel a payout address change that hasn't taken effect yet
+    pub fn cancel_payout_address(env: Env, merchant: Address) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        let mut merchant_data = match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => data,
+            None => return Err(Error::MerchantNotFound),
+        };
+        
+        merchant_data.payout_address = Some(Self::resolve_payout(&env, &merchant, &merchant_data));
+        merchant_data.pending_payout = None;
+        merchant_data.payout_effective_at = 0;
+        storage::set_merchant_data(&env, &merchant, &merchant_data);
+        
+        Ok(())
+    }
+    
+    /// Address that currently receives a merchant's payouts
+    pub fn get_merchant_payout(env: Env, merchant: Address) -> Address {
+        match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => Self::resolve_payout(&env, &merchant, &data),
+            None => merchant,
+        }
+    }
+    
+    /// Authorize or revoke a sub-key that can create bills but not change payouts
+    /// A sub-key can only belong to one merchant
+    pub fn set_bill_creator(
+        env: Env,
+        merchant: Address,
+        creator: Address,
+        authorized: bool,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        let mut merchant_data = match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => data,
+            None => return Err(Error::MerchantNotFound),
+        };
+        
+        let index = merchant_data.bill_creators.first_index_of(&creator);
+        match (index, authorized) {
+            (None, true) => {
+                if storage::get_bill_creator_merchant(&env, &creator).is_some() {
+                    return Err(Error::BillCreatorTaken);
+                }
+                if merchant_data.bill_creators.len() >= MAX_BILL_CREATORS {
+                    return Err(Error::TooManyBillCreators);
+                }
+                merchant_data.bill_creators.push_back(creator.clone());
+                storage::set_bill_creator_merchant(&env, &creator, &merchant);
+            }
+            (Some(i), false) => {
+                merchant_data.bill_creators.remove(i);
+                storage::remove_bill_creator_merchant(&env, &creator);
+            }
+            _ => {}
+        }
+        storage::set_merchant_data(&env, &merchant, &merchant_data);
+        
+        env.events().publish(
+            (symbol_short!("m_creator"), merchant.clone()),
+            BillCreatorUpdatedEvent {
+                merchant,
+                creator,
+                authorized,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    // internal function to resolve the payout address, applying a matured pending change
+    fn resolve_payout(env: &Env, merchant: &Address, data: &MerchantData) -> Address {
+        if let Some(pending) = data.pending_payout.clone() {
+            if env.ledger().timestamp() >= data.payout_effective_at {
+                return pending;
+            }
+        }
+        data.payout_address.clone().unwrap_or(merchant.clone())
+    }
+    
+    // internal function to get the payout address of a merchant
+    fn payout_of(env: &Env, merchant: &Address) -> Address {
+        Self::get_merchant_payout(env.clone(), merchant.clone())
+    }
+
     // === BILL MANAGEMENT ===
+
+    /// Create a bill in the default asset
+    /// `term` selects a promotional term from the merchant's menu, None for the standard term
     pub fn create_bill(
         env: Env,
         merchant: Address,
         user: Address,
         amount: i128,
         order_id: String,
+        term: Option<u32>,
+    ) -> u64 {
+        let asset = storage::get_config(&env).usdc_token;
+        let metadata = BillMetadata {
+            metadata_hash: None,
+            display_currency: None,
+            memo: None,
+            term,
+        };
+        Self::create_bill_with_metadata(env, merchant, user, asset, amount, order_id, metadata)
+    }
+
+    /// Create a bill denominated in any asset with a registered pool
+    pub fn create_bill_with_asset(
+        env: Env,
+        merchant: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        order_id: String,
+    ) -> u64 {
+        let metadata = BillMetadata {
+            metadata_hash: None,
+            display_currency: None,
+            memo: None,
+            term: None,
+        };
+        Self::create_bill_with_metadata(env, merchant, user, asset, amount, order_id, metadata)
+    }
+
+    /// Create a bill with a receipt commitment, display currency and memo
+    pub fn create_bill_with_metadata(
+        env: Env,
+        merchant: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        order_id: String,
+        metadata: BillMetadata,
     ) -> u64 {
         merchant.require_auth();
+        Self::new_bill(env, merchant, user, asset, amount, order_id, metadata)
+    }
+
+    /// Create a bill for the merchant that authorized this sub-key (e.g. a POS terminal)
+    pub fn create_bill_as_creator(
+        env: Env,
+        creator: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        order_id: String,
+        metadata: BillMetadata,
+    ) -> u64 {
+        creator.require_auth();
         
-        // Check if merchant is approved using new system
-        let merchant_data = storage::get_merchant_data(&env, &merchant);
-        if merchant_data.is_none() || merchant_data.unwrap().status != MerchantStatus::Approved {
-            panic!("Merchant not approved");
-        }
+        let merchant = match storage::get_bill_creator_merchant(&env, &creator) {
+            Some(merchant) => merchant,
+            None => panic_with_error!(&env, Error::NotBillCreator),
+        };
+        
+        Self::new_bill(env, merchant, user, asset, amount, order_id, metadata)
+    }
 
-        if amount <= 0 {
-            panic!("Invalid amount");
+    // internal function to create a bill once the caller is authorized
+    fn new_bill(
+        env: Env,
+        merchant: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        order_id: String,
+        metadata: BillMetadata,
+    ) -> u64 {
+        let term = match Self::check_new_bill(&env, &merchant, &asset, amount, &order_id, &metadata) {
+            Ok(term) => term,
+            Err(error) => panic_with_error!(&env, error),
+        };
+
+        // The order is free, so a previous bill still marked Created has expired
+        if let Some(previous_id) = storage::get_order_bill(&env, &merchant, &order_id) {
+            let mut previous = storage::get_bill(&env, previous_id);
+            if previous.status == BillStatus::Created {
+                previous.status = BillStatus::Expired;
+                storage::set_bill(&env, previous_id, &previous);
+            }
         }
 
         let bill_id = storage::get_bill_counter(&env);
@@ -227,15 +704,26 @@ impl UnifiedBNPLContract {
             id: bill_id,
             merchant: merchant.clone(),
             user: user.clone(),
+            asset,
             principal: amount,
             status: BillStatus::Created,
             order_id, // Offchain order ID
             created_at: env.ledger().timestamp(),
             paid_at: 0,
+            metadata_hash: metadata.metadata_hash,
+            display_currency: metadata.display_currency,
+            memo: metadata.memo,
+            term,
         };
 
         storage::set_bill(&env, bill_id, &bill);
         storage::set_bill_counter(&env, bill_id + 1);
+        
+        storage::add_merchant_bill(&env, &merchant, bill_id);
+        storage::set_order_bill(&env, &merchant, &bill.order_id, bill_id);
+        let mut stats = storage::get_merchant_stats(&env, &merchant, &bill.asset);
+        stats.bills_created += 1;
+        storage::set_merchant_stats(&env, &merchant, &bill.asset, &stats);
     
         env.events().publish(
             (soroban_sdk::symbol_short!("bill_new"), merchant, bill_id),
@@ -243,59 +731,673 @@ impl UnifiedBNPLContract {
                 bill_id,
                 merchant: bill.merchant,
                 user: bill.user,
+                asset: bill.asset,
                 amount: bill.principal,
                 order_id: bill.order_id,
                 created_at: bill.created_at,
+                metadata_hash: bill.metadata_hash,
+                display_currency: bill.display_currency,
+                memo: bill.memo,
             }
         );
 
         bill_id
     }
 
-    pub fn get_bill(env: Env, bill_id: u64) -> Bill {
-        storage::get_bill(&env, bill_id)
-    }
-    
-    pub fn get_user_bills(env: Env, user: Address) -> Vec<u64> {
-        storage::get_user_bills(&env, &user)
+    // internal function to validate a new bill and resolve its term
+    // Shared by single and batch creation so both apply the same rules
+    fn check_new_bill(
+        env: &Env,
+        merchant: &Address,
+        asset: &Address,
+        amount: i128,
+        order_id: &String,
+        metadata: &BillMetadata,
+    ) -> Result<BillTerm, Error> {
+        if !storage::is_merchant_approved(env, merchant) {
+            return Err(Error::MerchantNotApproved);
+        }
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if storage::get_pool(env, asset).is_none() {
+            return Err(Error::PoolNotFound);
+        }
+        if let Some(currency) = metadata.display_currency.clone() {
+            if currency.is_empty() || currency.len() > MAX_CURRENCY_CODE_LEN {
+                return Err(Error::InvalidInput);
+            }
+        }
+        if let Some(memo) = metadata.memo.clone() {
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(Error::InvalidInput);
+            }
+        }
+        let term = match metadata.term {
+            Some(index) => match storage::get_merchant_terms(env, merchant).get(index) {
+                Some(term) => term,
+                None => return Err(Error::InvalidTerm),
+            },
+            None => Self::standard_term(),
+        };
+
+        // An order can only be billed again once its previous bill is expired or cancelled
+        if let Some(previous_id) = storage::get_order_bill(env, merchant, order_id) {
+            let previous = storage::get_bill(env, previous_id);
+            let free = previous.status == BillStatus::Expired
+                || previous.status == BillStatus::Cancelled
+                || (previous.status == BillStatus::Created && Self::is_bill_expired(env, &previous));
+            if !free {
+                return Err(Error::DuplicateOrder);
+            }
+        }
+
+        Ok(term)
     }
 
-    pub fn pay_bill_bnpl(
+    /// Set the promotional terms a merchant can offer (admin only)
+    /// Each term must keep liquidation after its grace period
+    pub fn set_merchant_terms(
         env: Env,
-        bill_id: u64
-    ) {
-        let mut bill = storage::get_bill(&env, bill_id);
-        bill.user.require_auth();
+        admin: Address,
+        merchant: Address,
+        terms: Vec<BillTerm>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
         
-        // Validate bill
-        if bill.status != BillStatus::Created {
-            panic!("Bill not payable");
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
         }
-        if env.ledger().timestamp() > bill.created_at + BILL_DURATION_DAYS * SECONDS_PER_DAY {
-            panic!("Bill expired");
+        if terms.len() > MAX_MERCHANT_TERMS {
+            return Err(Error::InvalidInput);
+        }
+        for term in terms.iter() {
+            if term.fee_rate < 0 || term.fee_rate >= SCALE_7 || term.late_apr < 0 || term.liquidation_days < term.grace_days {
+                return Err(Error::InvalidTerm);
+            }
         }
-
-        let available_borrowing = Self::get_user_borrowing_power(env.clone(), bill.user.clone());
         
-        if available_borrowing.available_borrowing < bill.principal {
-            panic!("Insufficient collateral");
+        storage::set_merchant_terms(&env, &merchant, &terms);
+        Ok(())
+    }
+    
+    /// Promotional terms of a merchant; index `i` is selected with `term: Some(i)`
+    pub fn get_merchant_terms(env: Env, merchant: Address) -> Vec<BillTerm> {
+        storage::get_merchant_terms(&env, &merchant)
+    }
+    
+    // internal function to get the protocol's default repayment terms
+    fn standard_term() -> BillTerm {
+        BillTerm {
+            fee_rate: MERCHANT_FEE_RATE,
+            grace_days: GRACE_PERIOD_DAYS,
+            liquidation_days: LIQUIDATION_THRESHOLD_DAYS,
+            late_apr: LATE_INTEREST_APR,
         }
+    }
 
-        // Calculate merchant fee
-        let merchant_fee = (bill.principal * MERCHANT_FEE_RATE) / SCALE_7;
-        let merchant_receives = bill.principal - merchant_fee;
-        
-        // Transfer USDC to merchant (minus fee)
-        let config = storage::get_config(&env);
-        let liquidity_pool = config.liquidity_pool;
-        let liquidity_pool_client = LPTokenClient::new(&env, &liquidity_pool);
+    pub fn get_bill(env: Env, bill_id: u64) -> Bill {
+        storage::get_bill(&env, bill_id)
+    }
 
-        liquidity_pool_client.borrow(&env.current_contract_address(), &bill.principal);
+    /// Get the latest bill created for a merchant's order
+    pub fn get_bill_by_order(env: Env, merchant: Address, order_id: String) -> Option<Bill> {
+        storage::get_order_bill(&env, &merchant, &order_id).map(|bill_id| storage::get_bill(&env, bill_id))
+    }
+
+    /// Check a receipt preimage against the bill's stored metadata hash
+    pub fn verify_bill_metadata(env: Env, bill_id: u64, preimage: Bytes) -> bool {
+        let bill = storage::get_bill(&env, bill_id);
+        match bill.metadata_hash {
+            Some(hash) => env.crypto().sha256(&preimage).to_bytes() == hash,
+            None => false,
+        }
+    }
+
+    /// Cancel an unpaid bill (merchant only)
+    pub fn cancel_bill(env: Env, bill_id: u64) {
+        let mut bill = storage::get_bill(&env, bill_id);
+        bill.merchant.require_auth();
+
+        if bill.status != BillStatus::Created {
+            panic!("Bill not cancellable");
+        }
+
+        bill.status = BillStatus::Cancelled;
+        storage::set_bill(&env, bill_id, &bill);
+
+        env.events().publish(
+            (symbol_short!("bill_cncl"), bill.merchant.clone(), bill_id),
+            BillCancelledEvent {
+                bill_id,
+                merchant: bill.merchant,
+                order_id: bill.order_id,
+            }
+        );
+    }
+
+    // internal function to check whether an unpaid bill can no longer be paid
+    fn is_bill_expired(env: &Env, bill: &Bill) -> bool {
+        env.ledger().timestamp() > bill.created_at + BILL_DURATION_DAYS * SECONDS_PER_DAY
+    }
+    
+    /// Get all open (paid, not yet repaid) bills of a user
+    pub fn get_user_bills(env: Env, user: Address) -> Vec<u64> {
+        Self::get_user_bills_page(env.clone(), user, 0, MAX_OPEN_BILLS)
+    }
+
+    /// Get a page of a user's open bills starting at `cursor` (at most `MAX_PAGE_SIZE`)
+    pub fn get_user_bills_page(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<u64> {
+        let count = storage::get_user_bill_count(&env, &user);
+        let end = count.min(cursor.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        
+        let mut bills = Vec::new(&env);
+        for i in cursor..end {
+            bills.push_back(storage::get_user_bill_at(&env, &user, i));
+        }
+        bills
+    }
+
+    /// Get a page of every bill a user has paid, including repaid and liquidated ones, oldest first
+    pub fn get_user_bill_history(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<Bill> {
+        let count = storage::get_user_history_count(&env, &user);
+        let end = count.min(cursor.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        
+        let mut bills = Vec::new(&env);
+        for i in cursor..end {
+            bills.push_back(storage::get_bill(&env, storage::get_user_history_at(&env, &user, i)));
+        }
+        bills
+    }
 
+    /// Number of bills a merchant has created
+    pub fn get_merchant_bill_count(env: Env, merchant: Address) -> u32 {
+        storage::get_merchant_bill_count(&env, &merchant)
+    }
+
+    /// Page through a merchant's bills, oldest first, optionally keeping only one status
+    /// Scans at most `MAX_PAGE_SIZE` index entries per call; continue from `next_cursor`
+    pub fn get_merchant_bills(
+        env: Env,
+        merchant: Address,
+        cursor: u32,
+        limit: u32,
+        status: Option<BillStatus>,
+    ) -> BillPage {
+        let count = storage::get_merchant_bill_count(&env, &merchant);
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let end = count.min(cursor.saturating_add(MAX_PAGE_SIZE));
+        
+        let mut bills = Vec::new(&env);
+        let mut next_cursor = cursor;
+        while next_cursor < end && bills.len() < limit {
+            let bill = storage::get_bill(&env, storage::get_merchant_bill_at(&env, &merchant, next_cursor));
+            next_cursor += 1;
+            
+            if status.is_none() || status == Some(bill.status.clone()) {
+                bills.push_back(bill);
+            }
+        }
+        
+        BillPage {
+            bills,
+            next_cursor,
+        }
+    }
+
+    /// Settlement totals of a merchant in one asset
+    pub fn get_merchant_stats(env: Env, merchant: Address, asset: Address) -> MerchantStats {
+        storage::get_merchant_stats(&env, &merchant, &asset)
+    }
+
+    // === MERCHANT BOND ===
+
+    /// Cap the outstanding principal financed through a merchant (admin only)
+    /// A limit is required for approval, and the merchant must bond `MERCHANT_BOND_RATIO` of it
+    /// first. Stablecoin pools are counted at par.
+    pub fn set_merchant_exposure_limit(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        limit: i128,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        if limit < 0 {
+            return Err(Error::InvalidInput);
+        }
+        
+        storage::set_merchant_exposure_limit(&env, &merchant, limit);
+        Ok(())
+    }
+    
+    /// Outstanding principal financed through a merchant
+    pub fn get_merchant_exposure(env: Env, merchant: Address) -> i128 {
+        storage::get_merchant_exposure(&env, &merchant)
+    }
+    
+    /// Bond required for a merchant's exposure limit
+    /// 0 until a limit is set; approval fails without one (`ExposureLimitRequired`)
+    pub fn get_required_bond(env: Env, merchant: Address) -> i128 {
+        match storage::get_merchant_exposure_limit(&env, &merchant) {
+            Some(limit) => limit * MERCHANT_BOND_RATIO / SCALE_7,
+            None => 0,
+        }
+    }
+    
+    pub fn get_merchant_bond(env: Env, merchant: Address) -> MerchantBond {
+        storage::get_merchant_bond(&env, &merchant)
+    }
+    
+    /// Post a bond in the default asset
+    pub fn post_bond(env: Env, merchant: Address, amount: i128) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if storage::get_merchant_data(&env, &merchant).is_none() {
+            return Err(Error::MerchantNotFound);
+        }
+        
+        let config = storage::get_config(&env);
+        let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
+        usdc_client.transfer(&merchant, &env.current_contract_address(), &amount);
+        
+        let mut bond = storage::get_merchant_bond(&env, &merchant);
+        bond.amount += amount;
+        storage::set_merchant_bond(&env, &merchant, &bond);
+        
+        env.events().publish(
+            (symbol_short!("bond_post"), merchant.clone()),
+            BondEvent {
+                merchant,
+                amount,
+                lp_amount: 0,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Bond by locking LP tokens of the default pool in place
+    pub fn lock_lp_bond(env: Env, merchant: Address, amount: i128) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if storage::get_merchant_data(&env, &merchant).is_none() {
+            return Err(Error::MerchantNotFound);
+        }
+        
+        let mut bond = storage::get_merchant_bond(&env, &merchant);
+        let config = storage::get_config(&env);
+        let lp_client = LPTokenClient::new(&env, &config.liquidity_pool);
+        if lp_client.available_balance(&merchant) < amount {
+            return Err(Error::InsufficientBond);
+        }
+        
+        lp_client.lock_collateral(&merchant, &amount);
+        bond.lp_locked += amount;
+        storage::set_merchant_bond(&env, &merchant, &bond);
+        
+        env.events().publish(
+            (symbol_short!("bond_post"), merchant.clone()),
+            BondEvent {
+                merchant,
+                amount: 0,
+                lp_amount: amount,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Slash a merchant's bond into the insurance fund (admin only)
+    /// The posted bond is used first, then locked LP tokens are burned
+    pub fn slash_bond(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        
+        let bond = storage::get_merchant_bond(&env, &merchant);
+        if amount <= 0 || amount > bond.amount + bond.lp_locked {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let (from_bond, from_lp) = Self::take_bond(&env, &merchant, amount);
+        
+        let config = storage::get_config(&env);
         let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
-        usdc_client.transfer(&env.current_contract_address(), &bill.merchant, &merchant_receives);
+        usdc_client.transfer(&env.current_contract_address(), &config.insurance_fund, &amount);
+        
+        env.events().publish(
+            (symbol_short!("bond_slsh"), merchant.clone()),
+            BondEvent {
+                merchant,
+                amount: from_bond,
+                lp_amount: from_lp,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    /// Withdraw the full bond once the merchant is pending, rejected or cancelled and the cool-down
+    /// since its last status change has passed
+    pub fn unbond(env: Env, merchant: Address) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        let merchant_data = match storage::get_merchant_data(&env, &merchant) {
+            Some(data) => data,
+            None => return Err(Error::MerchantNotFound),
+        };
+        let unbondable = matches!(
+            merchant_data.status,
+            MerchantStatus::Pending | MerchantStatus::Rejected | MerchantStatus::Cancelled
+        );
+        if !unbondable
+            || env.ledger().timestamp() < merchant_data.status_updated_at + BOND_COOLDOWN_DAYS * SECONDS_PER_DAY
+        {
+            return Err(Error::BondLocked);
+        }
+        
+        let bond = storage::get_merchant_bond(&env, &merchant);
+        let config = storage::get_config(&env);
+        if bond.amount > 0 {
+            let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
+            usdc_client.transfer(&env.current_contract_address(), &merchant, &bond.amount);
+        }
+        if bond.lp_locked > 0 {
+            LPTokenClient::new(&env, &config.liquidity_pool).unlock_collateral(&merchant, &bond.lp_locked);
+        }
+        storage::set_merchant_bond(&env, &merchant, &MerchantBond { amount: 0, lp_locked: 0 });
+        
+        env.events().publish(
+            (symbol_short!("bond_out"), merchant.clone()),
+            BondEvent {
+                merchant,
+                amount: bond.amount,
+                lp_amount: bond.lp_locked,
+            }
+        );
+        
+        Ok(())
+    }
+    
+    // internal function to take up to `amount` of a merchant's bond, posted bond first and then locked LP,
+    // which is burned for its underlying; everything taken lands in this contract
+    // Returns the amounts taken from the posted bond and from LP
+    fn take_bond(env: &Env, merchant: &Address, amount: i128) -> (i128, i128) {
+        let mut bond = storage::get_merchant_bond(env, merchant);
+        let from_bond = amount.min(bond.amount);
+        let from_lp = (amount - from_bond).min(bond.lp_locked);
+        bond.amount -= from_bond;
+        bond.lp_locked -= from_lp;
+        storage::set_merchant_bond(env, merchant, &bond);
+        
+        if from_lp > 0 {
+            let config = storage::get_config(env);
+            let lp_client = LPTokenClient::new(env, &config.liquidity_pool);
+            lp_client.unlock_collateral(merchant, &from_lp);
+            lp_client.slash(merchant, &from_lp);
+        }
+        
+        (from_bond, from_lp)
+    }
+    
+    // internal function to check a merchant's bond covers its exposure limit
+    fn is_bond_sufficient(env: &Env, merchant: &Address) -> bool {
+        let bond = storage::get_merchant_bond(env, merchant);
+        bond.amount + bond.lp_locked >= Self::get_required_bond(env.clone(), merchant.clone())
+    }
+    
+    // internal function to track a newly paid bill in the user index, interest accrual and merchant exposure
+    fn open_bill(env: &Env, bill: &Bill) {
+        storage::add_user_bill(env, bill);
+        Self::track_interest(env, bill);
+        let exposure = storage::get_merchant_exposure(env, &bill.merchant);
+        storage::set_merchant_exposure(env, &bill.merchant, exposure + bill.principal);
+    }
+    
+    // internal function to drop a settled bill from the user index, interest accrual and merchant exposure
+    fn close_bill(env: &Env, bill: &Bill) {
+        storage::remove_user_bill(env, bill);
+        Self::untrack_interest(env, bill);
+        let exposure = storage::get_merchant_exposure(env, &bill.merchant);
+        storage::set_merchant_exposure(env, &bill.merchant, exposure - bill.principal);
+    }
+
+    // === MERCHANT RESERVE ===
+
+    /// Hold back `rate` (scaled by 10^7) of each payout to a merchant for at least `hold_days` (admin only)
+    /// A rate of 0 disables the reserve for new payouts
+    pub fn set_merchant_reserve(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        rate: i128,
+        hold_days: u64,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        
+        if !(0..=SCALE_7).contains(&rate) {
+            return Err(Error::InvalidInput);
+        }
+        
+        storage::set_reserve_config(&env, &merchant, &ReserveConfig { rate, hold_days });
+        Ok(())
+    }
+    
+    pub fn get_merchant_reserve_config(env: Env, merchant: Address) -> Option<ReserveConfig> {
+        storage::get_reserve_config(&env, &merchant)
+    }
+    
+    /// Pending versus releasable reserve of a merchant in one asset
+    pub fn get_merchant_reserve(env: Env, merchant: Address, asset: Address) -> ReserveBalance {
+        let now = env.ledger().timestamp();
+        let mut balance = ReserveBalance { pending: 0, releasable: 0 };
+        
+        for tranche in storage::get_reserve(&env, &merchant, &asset).iter() {
+            if tranche.release_at <= now {
+                balance.releasable += tranche.amount;
+            } else {
+                balance.pending += tranche.amount;
+            }
+        }
+        
+        balance
+    }
+    
+    /// Pay out a merchant's matured reserve; returns the amount released
+    pub fn release_reserve(env: Env, merchant: Address, asset: Address) -> i128 {
+        let tranches = storage::get_reserve(&env, &merchant, &asset);
+        let (released, remaining) = Self::release_matured(&env, &merchant, &asset, tranches);
+        if released > 0 {
+            storage::set_reserve(&env, &merchant, &asset, &remaining);
+        }
+        released
+    }
+    
+    // internal function to pay out the matured tranches of a reserve, less any merchant debt
+    // Returns the amount released and the tranches still held
+    fn release_matured(env: &Env, merchant: &Address, asset: &Address, tranches: Vec<ReserveTranche>) -> (i128, Vec<ReserveTranche>) {
+        let now = env.ledger().timestamp();
+        
+        let mut released = 0i128;
+        let mut remaining = Vec::new(env);
+        for tranche in tranches.iter() {
+            if tranche.release_at <= now {
+                released += tranche.amount;
+            } else {
+                remaining.push_back(tranche);
+            }
+        }
+        
+        if released > 0 {
+            let paid = released - Self::recover_merchant_debt(env, merchant, asset, released);
+            if paid > 0 {
+                let usdc_client = soroban_sdk::token::Client::new(env, asset);
+                usdc_client.transfer(&env.current_contract_address(), &Self::payout_of(env, merchant), &paid);
+            }
+            
+            env.events().publish(
+                (symbol_short!("rsv_rel"), merchant.clone()),
+                ReserveReleasedEvent {
+                    merchant: merchant.clone(),
+                    asset: asset.clone(),
+                    amount: released,
+                }
+            );
+        }
+        
+        (released, remaining)
+    }
+    
+    // internal function to hold back part of a payout; returns the amount held
+    // Matured tranches are paid out first and the new one is inserted in release order, merging with any
+    // tranche releasing the same day, so the reserve only holds one tranche per day still in its holding period
+    fn hold_reserve(env: &Env, merchant: &Address, asset: &Address, payout: i128) -> i128 {
+        let config = match storage::get_reserve_config(env, merchant) {
+            Some(config) if config.rate > 0 => config,
+            _ => return 0,
+        };
+        
+        let held = payout * config.rate / SCALE_7;
+        if held == 0 {
+            return 0;
+        }
+        
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let release_at = (today + 1 + config.hold_days) * SECONDS_PER_DAY;
+        
+        let (_, mut tranches) = Self::release_matured(env, merchant, asset, storage::get_reserve(env, merchant, asset));
+        
+        // `hold_days` can change between payouts, so the new tranche isn't necessarily the latest
+        let mut position = tranches.len();
+        while position > 0 && tranches.get(position - 1).unwrap().release_at > release_at {
+            position -= 1;
+        }
+        if position > 0 && tranches.get(position - 1).unwrap().release_at == release_at {
+            let mut tranche = tranches.get(position - 1).unwrap();
+            tranche.amount += held;
+            tranches.set(position - 1, tranche);
+        } else {
+            tranches.insert(position, ReserveTranche { amount: held, release_at });
+        }
+        storage::set_reserve(env, merchant, asset, &tranches);
+        
+        held
+    }
+    
+    // internal function to take up to `amount` out of a merchant's reserve, latest tranches first
+    // Returns the amount taken, which stays in this contract
+    fn take_reserve(env: &Env, merchant: &Address, asset: &Address, amount: i128) -> i128 {
+        let mut tranches = storage::get_reserve(env, merchant, asset);
+        let mut taken = 0i128;
+        
+        while taken < amount {
+            let mut last = match tranches.pop_back() {
+                Some(tranche) => tranche,
+                None => break,
+            };
+            
+            let take = (amount - taken).min(last.amount);
+            taken += take;
+            last.amount -= take;
+            if last.amount > 0 {
+                tranches.push_back(last);
+            }
+        }
+        
+        storage::set_reserve(env, merchant, asset, &tranches);
+        taken
+    }
+
+    pub fn pay_bill_bnpl(
+        env: Env,
+        bill_id: u64
+    ) {
+        let bill = storage::get_bill(&env, bill_id);
+        bill.user.require_auth();
+        
+        Self::finance_bill(env, bill);
+    }
+
+    // internal function to finance a created bill once the user is authorized
+    fn finance_bill(env: Env, mut bill: Bill) {
+        let bill_id = bill.id;
+        
+        // Validate bill
+        if bill.status != BillStatus::Created {
+            panic!("Bill not payable");
+        }
+        if Self::is_bill_expired(&env, &bill) {
+            panic!("Bill expired");
+        }
+        // Bills of suspended merchants are frozen, those of cancelled merchants can never be paid
+        if !storage::is_merchant_approved(&env, &bill.merchant) {
+            panic!("Merchant not approved");
+        }
+        if let Some(limit) = storage::get_merchant_exposure_limit(&env, &bill.merchant) {
+            if storage::get_merchant_exposure(&env, &bill.merchant) + bill.principal > limit {
+                panic!("Merchant exposure limit exceeded");
+            }
+        }
+
+        if storage::get_user_bill_count(&env, &bill.user) >= MAX_OPEN_BILLS {
+            panic!("Too many open bills");
+        }
+
+        let available_borrowing = Self::get_user_pool_borrowing_power(env.clone(), bill.user.clone(), bill.asset.clone());
+        
+        // Debt beyond LP-based borrowing power must be backed by posted collateral
+        if available_borrowing.available_borrowing < bill.principal
+            && !Self::collateral_covers(&env, &bill.user, Some((bill.asset.clone(), bill.principal)))
+        {
+            panic!("Insufficient collateral");
+        }
+
+        // Calculate merchant fee
+        let merchant_fee = (bill.principal * bill.term.fee_rate) / SCALE_7;
+        let merchant_receives = bill.principal - merchant_fee;
+        
+        // Transfer the bill asset to merchant (minus fee)
+        let liquidity_pool = Self::pool_for(&env, &bill.asset);
+        let liquidity_pool_client = LPTokenClient::new(&env, &liquidity_pool);
 
-        Self::distribute_fees(env.clone(), merchant_fee);
+        liquidity_pool_client.borrow(&env.current_contract_address(), &bill.principal);
+        Self::lock_bill_collateral(&env, &liquidity_pool_client, &bill, available_borrowing.available_borrowing.min(bill.principal));
+
+        let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+        let held = Self::hold_reserve(&env, &bill.merchant, &bill.asset, merchant_receives);
+        let recovered = Self::recover_merchant_debt(&env, &bill.merchant, &bill.asset, merchant_receives - held);
+        if merchant_receives - held - recovered > 0 {
+            usdc_client.transfer(&env.current_contract_address(), &Self::payout_of(&env, &bill.merchant), &(merchant_receives - held - recovered));
+        }
+
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Merchant, merchant_fee);
+
+        let mut stats = storage::get_merchant_stats(&env, &bill.merchant, &bill.asset);
+        stats.bills_paid += 1;
+        stats.financed_volume += bill.principal;
+        stats.fees_paid += merchant_fee;
+        storage::set_merchant_stats(&env, &bill.merchant, &bill.asset, &stats);
 
         // Update bill status and track who paid
         bill.status = BillStatus::Paid;
@@ -303,10 +1405,8 @@ impl UnifiedBNPLContract {
         
         storage::set_bill(&env, bill_id, &bill);
         
-        // Add bill to user bills list after payment
-        let mut user_bills = storage::get_user_bills(&env, &bill.user);
-        user_bills.push_back(bill_id);
-        storage::set_user_bills(&env, &bill.user, &user_bills);
+        // Add bill to user bills index after payment
+        Self::open_bill(&env, &bill);
 
         env.events().publish(
             (soroban_sdk::symbol_short!("payment"), bill.user.clone(), bill_id),
@@ -316,178 +1416,937 @@ impl UnifiedBNPLContract {
                 merchant: bill.merchant,
                 total_paid: bill.principal,
             }
-        );
-
+        );
+
+    }
+
+    // === SIGNED CHECKOUT ===
+
+    /// Register the ed25519 key whose signatures authorize checkout offers (merchant only)
+    pub fn set_checkout_key(env: Env, merchant: Address, public_key: BytesN<32>) -> Result<(), Error> {
+        merchant.require_auth();
+        
+        if storage::get_merchant_data(&env, &merchant).is_none() {
+            return Err(Error::MerchantNotFound);
+        }
+        
+        storage::set_checkout_key(&env, &merchant, &public_key);
+        Ok(())
+    }
+    
+    pub fn get_checkout_key(env: Env, merchant: Address) -> Option<BytesN<32>> {
+        storage::get_checkout_key(&env, &merchant)
+    }
+    
+    pub fn is_offer_nonce_used(env: Env, merchant: Address, nonce: u64) -> bool {
+        storage::is_nonce_used(&env, &merchant, nonce)
+    }
+    
+    /// Bytes the merchant signs for an offer: this contract's address followed by the offer, XDR encoded
+    pub fn checkout_payload(env: Env, offer: BillOffer) -> Bytes {
+        let mut payload = env.current_contract_address().to_xdr(&env);
+        payload.append(&offer.to_xdr(&env));
+        payload
+    }
+    
+    /// Create and finance a bill from a merchant-signed offer in one transaction (user only)
+    /// The offer names its user, so a leaked offer can't be taken by someone else; each offer nonce
+    /// can be used once and an invalid signature aborts the transaction
+    pub fn checkout(env: Env, user: Address, offer: BillOffer, merchant_signature: BytesN<64>) -> u64 {
+        user.require_auth();
+        
+        if offer.user != user {
+            panic_with_error!(&env, Error::OfferNotForUser);
+        }
+        if env.ledger().timestamp() > offer.expiry {
+            panic_with_error!(&env, Error::OfferExpired);
+        }
+        if storage::is_nonce_used(&env, &offer.merchant, offer.nonce) {
+            panic_with_error!(&env, Error::NonceUsed);
+        }
+        let public_key = match storage::get_checkout_key(&env, &offer.merchant) {
+            Some(public_key) => public_key,
+            None => panic_with_error!(&env, Error::InvalidSignature),
+        };
+        let payload = Self::checkout_payload(env.clone(), offer.clone());
+        env.crypto().ed25519_verify(&public_key, &payload, &merchant_signature);
+        
+        storage::use_nonce(&env, &offer.merchant, offer.nonce);
+        
+        let metadata = BillMetadata {
+            metadata_hash: None,
+            display_currency: None,
+            memo: None,
+            term: offer.term,
+        };
+        let bill_id = Self::new_bill(env.clone(), offer.merchant, offer.user, offer.asset, offer.amount, offer.order_id, metadata);
+        Self::finance_bill(env.clone(), storage::get_bill(&env, bill_id));
+        
+        bill_id
+    }
+
+    // === LOAN MANAGEMENT ===
+    pub fn repay_bill(env: Env, bill_id: u64) {
+        let bill = storage::get_bill(&env, bill_id);
+        bill.user.require_auth();
+
+        if bill.status != BillStatus::Paid {
+            panic!("Bill not paid");
+        }
+        
+        let payer = bill.user.clone();
+        Self::settle_bill(env, bill, payer);
+    }
+
+    /// Repay someone else's bill (e.g. an employer or parent), funded by the payer's allowance
+    pub fn repay_bill_for(env: Env, payer: Address, bill_id: u64) {
+        payer.require_auth();
+        let bill = storage::get_bill(&env, bill_id);
+
+        if bill.status != BillStatus::Paid {
+            panic!("Bill not paid");
+        }
+        
+        Self::settle_bill(env, bill, payer);
+    }
+
+    // internal function to repay a paid bill from the payer's allowance to this contract
+    fn settle_bill(env: Env, bill: Bill, payer: Address) {
+        let liquidity_pool = Self::pool_for(&env, &bill.asset);
+        let liquidity_pool_client = LPTokenClient::new(&env, &liquidity_pool);
+
+        let late_fee = Self::calc_late_fee(&env, &bill);
+
+        // Transfer the bill asset from borrower
+        let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+        usdc_client.transfer_from(&env.current_contract_address(), &payer, &env.current_contract_address(), &(bill.principal + late_fee));
+
+        usdc_client.approve(&env.current_contract_address(), &liquidity_pool, &(bill.principal + late_fee), &200);
+        liquidity_pool_client.repay(&env.current_contract_address(), &bill.principal);
+        Self::release_bill_collateral(&env, &liquidity_pool_client, &bill);
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Late, late_fee);
+
+        Self::mark_repaid(&env, bill);
+    }
+
+    /// Repay a bill by burning the user's own LP shares in the bill's pool (user only)
+    /// Self-repayment carries no liquidation penalty; at most `max_lp` raw shares are burned
+    pub fn repay_with_collateral(env: Env, bill_id: u64, max_lp: u128) -> u128 {
+        let bill = storage::get_bill(&env, bill_id);
+        bill.user.require_auth();
+
+        if bill.status != BillStatus::Paid {
+            panic!("Bill not paid");
+        }
+        
+        let liquidity_pool = Self::pool_for(&env, &bill.asset);
+        let liquidity_pool_client = LPTokenClient::new(&env, &liquidity_pool);
+
+        let late_fee = Self::calc_late_fee(&env, &bill);
+
+        // Release the bill's lock so its shares can be burned
+        Self::release_bill_collateral(&env, &liquidity_pool_client, &bill);
+        let shares_burned = liquidity_pool_client.redeem_to_repay(&bill.user, &bill.principal, &late_fee, &max_lp);
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Late, late_fee);
+
+        Self::mark_repaid(&env, bill);
+        
+        shares_burned
+    }
+
+    // internal function to close a bill whose debt has been returned to the pool
+    fn mark_repaid(env: &Env, mut bill: Bill) {
+        let bill_id = bill.id;
+        bill.status = BillStatus::Repaid;
+
+        // Update loan
+        storage::set_bill(env, bill_id, &bill);
+        
+        // Remove bill from user bills list after repayment
+        Self::close_bill(env, &bill);
+
+        env.events().publish(
+            (soroban_sdk::symbol_short!("repayment"), bill.user.clone(), bill_id),
+            RepaymentEvent {
+                bill_id,
+                user: bill.user,
+                amount_paid: bill.principal,
+                timestamp: env.ledger().timestamp(),
+            }
+        );
+    }
+
+    // === DEBT TRANSFER ===
+
+    /// Offer a paid bill's debt to another account (current debtor only)
+    /// The debt only moves once the new debtor accepts; a new offer replaces the pending one
+    pub fn transfer_bill_debt(env: Env, bill_id: u64, new_user: Address) {
+        let bill = storage::get_bill(&env, bill_id);
+        bill.user.require_auth();
+
+        if bill.status != BillStatus::Paid {
+            panic!("Bill not paid");
+        }
+        if new_user == bill.user {
+            panic!("Invalid debtor");
+        }
+        
+        storage::set_debt_transfer(&env, bill_id, &new_user);
+        
+        env.events().publish(
+            (symbol_short!("debt_req"), bill.user.clone(), bill_id),
+            DebtTransferEvent {
+                bill_id,
+                from: bill.user,
+                to: new_user,
+            }
+        );
+    }
+    
+    /// Withdraw a pending debt transfer offer (current debtor only)
+    pub fn cancel_bill_debt_transfer(env: Env, bill_id: u64) {
+        let bill = storage::get_bill(&env, bill_id);
+        bill.user.require_auth();
+        
+        storage::remove_debt_transfer(&env, bill_id);
+    }
+    
+    pub fn get_pending_debt_transfer(env: Env, bill_id: u64) -> Option<Address> {
+        storage::get_debt_transfer(&env, bill_id)
+    }
+    
+    /// Take over a bill's debt offered to this account
+    /// The new debtor needs borrowing power for the principal like a new purchase
+    pub fn accept_bill_debt(env: Env, new_user: Address, bill_id: u64) {
+        new_user.require_auth();
+        
+        let mut bill = storage::get_bill(&env, bill_id);
+        if storage::get_debt_transfer(&env, bill_id) != Some(new_user.clone()) {
+            panic!("No pending transfer");
+        }
+        if bill.status != BillStatus::Paid {
+            panic!("Bill not paid");
+        }
+        if storage::get_user_bill_count(&env, &new_user) >= MAX_OPEN_BILLS {
+            panic!("Too many open bills");
+        }
+        
+        let available_borrowing = Self::get_user_pool_borrowing_power(env.clone(), new_user.clone(), bill.asset.clone());
+        if available_borrowing.available_borrowing < bill.principal
+            && !Self::collateral_covers(&env, &new_user, Some((bill.asset.clone(), bill.principal)))
+        {
+            panic!("Insufficient collateral");
+        }
+        
+        storage::remove_debt_transfer(&env, bill_id);
+        
+        // Move the LP lock, open-bill index and interest accrual to the new debtor; merchant exposure is unchanged
+        let liquidity_pool_client = LPTokenClient::new(&env, &Self::pool_for(&env, &bill.asset));
+        Self::release_bill_collateral(&env, &liquidity_pool_client, &bill);
+        storage::remove_user_bill(&env, &bill);
+        Self::untrack_interest(&env, &bill);
+        
+        let previous_user = bill.user.clone();
+        bill.user = new_user.clone();
+        storage::set_bill(&env, bill_id, &bill);
+        
+        Self::lock_bill_collateral(&env, &liquidity_pool_client, &bill, available_borrowing.available_borrowing.min(bill.principal));
+        storage::add_user_bill(&env, &bill);
+        Self::track_interest(&env, &bill);
+        
+        env.events().publish(
+            (symbol_short!("debt_xfer"), new_user.clone(), bill_id),
+            DebtTransferEvent {
+                bill_id,
+                from: previous_user,
+                to: new_user,
+            }
+        );
+    }
+
+    // === AUTO-REPAY ===
+
+    /// Opt into keeper repayment of due bills, capped per bill (0 opts out)
+    /// Keepers draw the repayment and their tip from the user's allowance to this contract
+    pub fn set_auto_repay(env: Env, user: Address, max_amount: i128) -> Result<(), Error> {
+        user.require_auth();
+        
+        if max_amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        if max_amount == 0 {
+            storage::remove_auto_repay(&env, &user);
+        } else {
+            storage::set_auto_repay(&env, &user, max_amount);
+        }
+        Ok(())
+    }
+    
+    pub fn get_auto_repay(env: Env, user: Address) -> i128 {
+        storage::get_auto_repay(&env, &user).unwrap_or(0)
+    }
+    
+    pub fn get_auto_repay_record(env: Env, bill_id: u64) -> Option<AutoRepayRecord> {
+        storage::get_auto_repay_record(&env, bill_id)
+    }
+    
+    /// Repay a due bill of an opted-in user (permissionless)
+    /// Bills are due from `AUTO_REPAY_WINDOW_DAYS` before their grace period ends. Attempts the
+    /// user's allowance, balance or cap can't cover are recorded without repaying.
+    pub fn execute_auto_repay(env: Env, keeper: Address, bill_id: u64) -> Result<AutoRepayStatus, Error> {
+        keeper.require_auth();
+        
+        let bill = storage::get_bill(&env, bill_id);
+        if bill.status != BillStatus::Paid {
+            return Err(Error::BillNotPaid);
+        }
+        let max_amount = match storage::get_auto_repay(&env, &bill.user) {
+            Some(max_amount) => max_amount,
+            None => return Err(Error::AutoRepayDisabled),
+        };
+        
+        let grace_end = bill.created_at + Self::dispute_pause(&env, bill_id) + bill.term.grace_days * SECONDS_PER_DAY;
+        if env.ledger().timestamp() + AUTO_REPAY_WINDOW_DAYS * SECONDS_PER_DAY < grace_end {
+            return Err(Error::BillNotDue);
+        }
+        
+        let late_fee = Self::calc_late_fee(&env, &bill);
+        let tip = bill.principal * KEEPER_TIP_RATE / SCALE_7;
+        let amount = bill.principal + late_fee + tip;
+        
+        let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+        let status = if amount > max_amount {
+            AutoRepayStatus::OverMaxAmount
+        } else if usdc_client.allowance(&bill.user, &env.current_contract_address()) < amount {
+            AutoRepayStatus::InsufficientAllowance
+        } else if usdc_client.balance(&bill.user) < amount {
+            AutoRepayStatus::InsufficientBalance
+        } else {
+            AutoRepayStatus::Repaid
+        };
+        
+        if status == AutoRepayStatus::Repaid {
+            let user = bill.user.clone();
+            Self::settle_bill(env.clone(), bill, user.clone());
+            usdc_client.transfer_from(&env.current_contract_address(), &user, &keeper, &tip);
+        }
+        
+        storage::set_auto_repay_record(&env, bill_id, &AutoRepayRecord {
+            status: status.clone(),
+            amount,
+            keeper: keeper.clone(),
+            timestamp: env.ledger().timestamp(),
+        });
+        
+        env.events().publish(
+            (symbol_short!("auto_rpy"), keeper.clone(), bill_id),
+            AutoRepayEvent {
+                bill_id,
+                keeper,
+                status: status.clone(),
+                amount,
+            }
+        );
+        
+        Ok(status)
+    }
+
+    // === LIQUIDATION ===
+    pub fn liquidate_bill(
+        env: Env,
+        bill_id: u64,
+        liquidator: Address,
+    ) {
+        liquidator.require_auth();
+        
+        let mut bill = storage::get_bill(&env, bill_id);
+        
+        // Check if liquidator holds LP tokens of the bill's pool
+        let liquidity_pool = Self::pool_for(&env, &bill.asset);
+        let lp_token_client = LPTokenClient::new(&env, &liquidity_pool);
+        let lp_balance = lp_token_client.balance(&liquidator);
+        
+        if lp_balance == 0 {
+            panic!("Not LP token holder");
+        }
+        
+        if env.ledger().timestamp() < bill.created_at + Self::dispute_pause(&env, bill_id) + (bill.term.liquidation_days * SECONDS_PER_DAY) {
+            panic!("Grace period not expired");
+        }
+        
+        if !(bill.status == BillStatus::Paid || bill.status == BillStatus::Overdue) {
+            panic!("Liquidation not possible");
+        }
+
+        bill.status = BillStatus::Liquidated;
+        storage::set_bill(&env, bill_id, &bill);
+        
+        // Remove bill from user bills list after liquidation
+        Self::close_bill(&env, &bill);
+
+        let late_fee = Self::calc_late_fee(&env, &bill);
+        let liquidation_fee = bill.principal * LIQUIDATION_PENALTY / SCALE_7;
+
+        let total_liquidated = bill.principal + late_fee + liquidation_fee;
+        
+        Self::release_bill_collateral(&env, &lp_token_client, &bill);
+        lp_token_client.repay_with_burn(&bill.user, &bill.principal, &(late_fee+liquidation_fee));
+
+        let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+        // Liquidator gets half the penalty; the fee split gets the rest, including any odd unit
+        usdc_client.transfer(&env.current_contract_address(), &liquidator, &(liquidation_fee / 2));
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Liquidation, liquidation_fee - liquidation_fee / 2);
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Late, late_fee);
+        
+        env.events().publish(
+            (soroban_sdk::symbol_short!("liquidate"), liquidator.clone(), bill_id),
+            LiquidationEvent {
+                bill_id,
+                liquidator,
+                total_liquidated,
+            }
+        );
+    }
+
+    // LP value to lock for `principal` of a bill: the most it can owe under its term
+    // (late fees up to the liquidation deadline plus the liquidation penalty), and never less than COLLATERAL_RATIO
+    fn collateral_lock(principal: i128, term: &BillTerm) -> i128 {
+        let late_days = (term.liquidation_days - term.grace_days) as i128;
+        let max_late_fee = (principal * term.late_apr * late_days) / (365 * SCALE_7);
+        let max_debt = principal + max_late_fee + principal * LIQUIDATION_PENALTY / SCALE_7;
+        max_debt.max(principal * COLLATERAL_RATIO / SCALE_7)
+    }
+
+    // internal function to lock the LP-backed part of a bill's debt in its pool
+    // Capped at the debtor's available LP balance; debt beyond it is backed by posted collateral
+    fn lock_bill_collateral(env: &Env, lp_client: &LPTokenClient, bill: &Bill, lp_backed: i128) {
+        let lock = Self::collateral_lock(lp_backed, &bill.term).min(lp_client.available_balance(&bill.user));
+        if lock > 0 {
+            lp_client.lock_collateral(&bill.user, &lock);
+            storage::set_bill_lock(env, bill.id, lock);
+            let locks = storage::get_user_bill_locks(env, &bill.user, &bill.asset);
+            storage::set_user_bill_locks(env, &bill.user, &bill.asset, locks + lock);
+        }
+    }
+
+    // internal function to release the LP locked for a bill
+    fn release_bill_collateral(env: &Env, lp_client: &LPTokenClient, bill: &Bill) {
+        let lock = storage::get_bill_lock(env, bill.id);
+        if lock > 0 {
+            lp_client.unlock_collateral(&bill.user, &lock);
+            storage::remove_bill_lock(env, bill.id);
+            let locks = storage::get_user_bill_locks(env, &bill.user, &bill.asset);
+            storage::set_user_bill_locks(env, &bill.user, &bill.asset, locks - lock);
+        }
+    }
+
+    // Seconds of late interest a bill has accrued: from the end of its grace period, pushed back by any
+    // dispute, until its liquidation deadline, which bounds the debt the bill's lock has to cover
+    fn late_seconds(env: &Env, bill: &Bill) -> u64 {
+        let grace_end = bill.created_at + Self::dispute_pause(env, bill.id) + bill.term.grace_days * SECONDS_PER_DAY;
+        let max_late = (bill.term.liquidation_days - bill.term.grace_days) * SECONDS_PER_DAY;
+        env.ledger().timestamp().saturating_sub(grace_end).min(max_late)
+    }
+
+    fn calc_late_fee(env: &Env, bill: &Bill) -> i128 {
+        bill.principal * bill.term.late_apr * Self::late_seconds(env, bill) as i128 / LATE_FEE_DENOMINATOR
+    }
+
+    // === INTEREST ACCRUAL ===
+
+    // Bring an accrual up to `now`, applying every scheduled rate change on the way
+    fn accrue_interest(accrual: &mut InterestAccrual, now: u64) {
+        while let Some(change) = accrual.schedule.first() {
+            if change.at > now {
+                break;
+            }
+            accrual.accrued += accrual.rate * (change.at - accrual.updated_at) as i128;
+            accrual.updated_at = change.at;
+            accrual.rate += change.delta;
+            accrual.schedule.pop_front();
+        }
+        accrual.accrued += accrual.rate * (now - accrual.updated_at) as i128;
+        accrual.updated_at = now;
+    }
+
+    fn schedule_rate_change(accrual: &mut InterestAccrual, change: RateChange) {
+        let mut position = 0;
+        while position < accrual.schedule.len() && accrual.schedule.get(position).unwrap().at <= change.at {
+            position += 1;
+        }
+        accrual.schedule.insert(position, change);
+    }
+
+    // Schedule the rest of a bill's late-interest window, starting it now if the grace period is over
+    fn schedule_bill_interest(env: &Env, accrual: &mut InterestAccrual, bill: &Bill) {
+        let now = env.ledger().timestamp();
+        let rate = bill.principal * bill.term.late_apr;
+        let start = bill.created_at + Self::dispute_pause(env, bill.id) + bill.term.grace_days * SECONDS_PER_DAY;
+        let end = start + (bill.term.liquidation_days - bill.term.grace_days) * SECONDS_PER_DAY;
+        
+        if now < start {
+            Self::schedule_rate_change(accrual, RateChange { at: start, bill_id: bill.id, delta: rate });
+        } else if now < end {
+            accrual.rate += rate;
+        }
+        if now < end {
+            Self::schedule_rate_change(accrual, RateChange { at: end, bill_id: bill.id, delta: -rate });
+        }
+    }
+
+    // Drop a bill's upcoming rate changes; their sum undoes whatever rate the bill has running
+    fn unschedule_bill_interest(env: &Env, accrual: &mut InterestAccrual, bill_id: u64) {
+        let mut schedule = Vec::new(env);
+        for change in accrual.schedule.iter() {
+            if change.bill_id == bill_id {
+                accrual.rate += change.delta;
+            } else {
+                schedule.push_back(change);
+            }
+        }
+        accrual.schedule = schedule;
+    }
+
+    // internal function to add a newly opened bill to its user's interest accrual
+    fn track_interest(env: &Env, bill: &Bill) {
+        let mut accrual = storage::get_user_interest(env, &bill.user, &bill.asset);
+        Self::accrue_interest(&mut accrual, env.ledger().timestamp());
+        accrual.accrued += bill.principal * bill.term.late_apr * Self::late_seconds(env, bill) as i128;
+        Self::schedule_bill_interest(env, &mut accrual, bill);
+        storage::set_user_interest(env, &bill.user, &bill.asset, &accrual);
+    }
+
+    // internal function to remove a settled bill and the interest it accrued from its user's accrual
+    fn untrack_interest(env: &Env, bill: &Bill) {
+        let mut accrual = storage::get_user_interest(env, &bill.user, &bill.asset);
+        Self::accrue_interest(&mut accrual, env.ledger().timestamp());
+        Self::unschedule_bill_interest(env, &mut accrual, bill.id);
+        accrual.accrued -= bill.principal * bill.term.late_apr * Self::late_seconds(env, bill) as i128;
+        storage::set_user_interest(env, &bill.user, &bill.asset, &accrual);
+    }
+
+    // internal function to freeze a disputed bill's interest, keeping what it has accrued so far
+    fn pause_interest(env: &Env, bill: &Bill) {
+        let mut accrual = storage::get_user_interest(env, &bill.user, &bill.asset);
+        Self::accrue_interest(&mut accrual, env.ledger().timestamp());
+        Self::unschedule_bill_interest(env, &mut accrual, bill.id);
+        storage::set_user_interest(env, &bill.user, &bill.asset, &accrual);
+    }
+
+    // internal function to resume a bill's interest once its dispute is ruled against the user
+    fn resume_interest(env: &Env, bill: &Bill) {
+        let mut accrual = storage::get_user_interest(env, &bill.user, &bill.asset);
+        Self::accrue_interest(&mut accrual, env.ledger().timestamp());
+        Self::schedule_bill_interest(env, &mut accrual, bill);
+        storage::set_user_interest(env, &bill.user, &bill.asset, &accrual);
+    }
+
+    // === BATCH OPERATIONS ===
+
+    /// Create several bills for one merchant
+    /// Invalid requests are skipped and reported with their error code
+    pub fn create_bills(env: Env, merchant: Address, requests: Vec<BillRequest>) -> Result<Vec<BatchResult>, Error> {
+        merchant.require_auth();
+        
+        if requests.len() > MAX_BATCH_SIZE {
+            return Err(Error::InvalidInput);
+        }
+        if !storage::is_merchant_approved(&env, &merchant) {
+            return Err(Error::MerchantNotApproved);
+        }
+        
+        let mut results = Vec::new(&env);
+        for request in requests.iter() {
+            let metadata = BillMetadata {
+                metadata_hash: None,
+                display_currency: None,
+                memo: None,
+                term: request.term,
+            };
+            let result = match Self::check_new_bill(&env, &merchant, &request.asset, request.amount, &request.order_id, &metadata) {
+                Ok(_) => BatchResult {
+                    bill_id: Self::new_bill(env.clone(), merchant.clone(), request.user, request.asset, request.amount, request.order_id, metadata),
+                    error: 0,
+                },
+                Err(error) => BatchResult { bill_id: 0, error: error as u32 },
+            };
+            results.push_back(result);
+        }
+        
+        Ok(results)
+    }
+    
+    /// Repay several bills funded by the payer's allowance
+    /// Each pool is repaid and each asset's late fees distributed once for the batch
+    pub fn repay_bills(env: Env, payer: Address, bill_ids: Vec<u64>) -> Result<Vec<BatchResult>, Error> {
+        payer.require_auth();
+        
+        if bill_ids.len() > MAX_BATCH_SIZE {
+            return Err(Error::InvalidInput);
+        }
+        
+        let mut pulled: Map<Address, i128> = Map::new(&env);
+        let mut principals: Map<Address, i128> = Map::new(&env);
+        let mut fees: Map<Address, i128> = Map::new(&env);
+        let mut results = Vec::new(&env);
+        for bill_id in bill_ids.iter() {
+            if !storage::has_bill(&env, bill_id) {
+                results.push_back(BatchResult { bill_id, error: Error::BillNotFound as u32 });
+                continue;
+            }
+            let bill = storage::get_bill(&env, bill_id);
+            if bill.status != BillStatus::Paid {
+                results.push_back(BatchResult { bill_id, error: Error::BillNotPaid as u32 });
+                continue;
+            }
+            
+            let late_fee = Self::calc_late_fee(&env, &bill);
+            let total = pulled.get(bill.asset.clone()).unwrap_or(0) + bill.principal + late_fee;
+            let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+            if usdc_client.allowance(&payer, &env.current_contract_address()) < total || usdc_client.balance(&payer) < total {
+                results.push_back(BatchResult { bill_id, error: Error::InsufficientFunds as u32 });
+                continue;
+            }
+            
+            pulled.set(bill.asset.clone(), total);
+            principals.set(bill.asset.clone(), principals.get(bill.asset.clone()).unwrap_or(0) + bill.principal);
+            fees.set(bill.asset.clone(), fees.get(bill.asset.clone()).unwrap_or(0) + late_fee);
+            
+            Self::release_bill_collateral(&env, &LPTokenClient::new(&env, &Self::pool_for(&env, &bill.asset)), &bill);
+            Self::mark_repaid(&env, bill);
+            results.push_back(BatchResult { bill_id, error: 0 });
+        }
+        
+        for (asset, total) in pulled.iter() {
+            let liquidity_pool = Self::pool_for(&env, &asset);
+            let principal = principals.get(asset.clone()).unwrap_or(0);
+            let usdc_client = soroban_sdk::token::Client::new(&env, &asset);
+            usdc_client.transfer_from(&env.current_contract_address(), &payer, &env.current_contract_address(), &total);
+            usdc_client.approve(&env.current_contract_address(), &liquidity_pool, &principal, &env.ledger().sequence());
+            LPTokenClient::new(&env, &liquidity_pool).repay(&env.current_contract_address(), &principal);
+            
+            Self::distribute_fees(env.clone(), asset.clone(), FeeType::Late, fees.get(asset.clone()).unwrap_or(0));
+        }
+        
+        Ok(results)
+    }
+    
+    /// Liquidate several bills past their liquidation threshold
+    /// Each pool burns and updates its index once, and each asset's fees are distributed once
+    pub fn liquidate_bills(env: Env, liquidator: Address, bill_ids: Vec<u64>) -> Result<Vec<BatchResult>, Error> {
+        liquidator.require_auth();
+        
+        if bill_ids.len() > MAX_BATCH_SIZE {
+            return Err(Error::InvalidInput);
+        }
+        
+        // Per asset: each user's (amount, fee) to burn, the liquidator's reward, the protocol's half
+        // of the penalty (which keeps any odd unit) and the late fees
+        let mut burns: Map<Address, Map<Address, (i128, i128)>> = Map::new(&env);
+        let mut rewards: Map<Address, i128> = Map::new(&env);
+        let mut penalties: Map<Address, i128> = Map::new(&env);
+        let mut fees: Map<Address, i128> = Map::new(&env);
+        let mut results = Vec::new(&env);
+        for bill_id in bill_ids.iter() {
+            if !storage::has_bill(&env, bill_id) {
+                results.push_back(BatchResult { bill_id, error: Error::BillNotFound as u32 });
+                continue;
+            }
+            let mut bill = storage::get_bill(&env, bill_id);
+            if !(bill.status == BillStatus::Paid || bill.status == BillStatus::Overdue) {
+                results.push_back(BatchResult { bill_id, error: Error::LiquidationNotPossible as u32 });
+                continue;
+            }
+            if env.ledger().timestamp() < bill.created_at + Self::dispute_pause(&env, bill_id) + (bill.term.liquidation_days * SECONDS_PER_DAY) {
+                results.push_back(BatchResult { bill_id, error: Error::GracePeriodNotExpired as u32 });
+                continue;
+            }
+            
+            let lp_token_client = LPTokenClient::new(&env, &Self::pool_for(&env, &bill.asset));
+            if lp_token_client.balance(&liquidator) == 0 {
+                results.push_back(BatchResult { bill_id, error: Error::NonLpTokenHolder as u32 });
+                continue;
+            }
+            
+            let late_fee = Self::calc_late_fee(&env, &bill);
+            let liquidation_fee = bill.principal * LIQUIDATION_PENALTY / SCALE_7;
+            
+            // The user's shares must cover all of their bills liquidated in this batch
+            let mut pool_burns = burns.get(bill.asset.clone()).unwrap_or(Map::new(&env));
+            let (amount, fee) = pool_burns.get(bill.user.clone()).unwrap_or((0, 0));
+            let (amount, fee) = (amount + bill.principal, fee + late_fee + liquidation_fee);
+            if lp_token_client.balance(&bill.user) < amount + fee {
+                results.push_back(BatchResult { bill_id, error: Error::InsufficientCollateralForLiquidation as u32 });
+                continue;
+            }
+            pool_burns.set(bill.user.clone(), (amount, fee));
+            burns.set(bill.asset.clone(), pool_burns);
+            rewards.set(bill.asset.clone(), rewards.get(bill.asset.clone()).unwrap_or(0) + liquidation_fee / 2);
+            penalties.set(bill.asset.clone(), penalties.get(bill.asset.clone()).unwrap_or(0) + liquidation_fee - liquidation_fee / 2);
+            fees.set(bill.asset.clone(), fees.get(bill.asset.clone()).unwrap_or(0) + late_fee);
+            
+            bill.status = BillStatus::Liquidated;
+            storage::set_bill(&env, bill_id, &bill);
+            Self::close_bill(&env, &bill);
+            Self::release_bill_collateral(&env, &lp_token_client, &bill);
+            
+            env.events().publish(
+                (soroban_sdk::symbol_short!("liquidate"), liquidator.clone(), bill_id),
+                LiquidationEvent {
+                    bill_id,
+                    liquidator: liquidator.clone(),
+                    total_liquidated: bill.principal + late_fee + liquidation_fee,
+                }
+            );
+            results.push_back(BatchResult { bill_id, error: 0 });
+        }
+        
+        for (asset, pool_burns) in burns.iter() {
+            let mut batch = Vec::new(&env);
+            for (user, (amount, fee)) in pool_burns.iter() {
+                batch.push_back((user, amount, fee));
+            }
+            LPTokenClient::new(&env, &Self::pool_for(&env, &asset)).repay_with_burn_batch(&batch);
+            
+            let usdc_client = soroban_sdk::token::Client::new(&env, &asset);
+            let reward = rewards.get(asset.clone()).unwrap_or(0);
+            if reward > 0 {
+                usdc_client.transfer(&env.current_contract_address(), &liquidator, &reward);
+            }
+            Self::distribute_fees(env.clone(), asset.clone(), FeeType::Liquidation, penalties.get(asset.clone()).unwrap_or(0));
+            Self::distribute_fees(env.clone(), asset.clone(), FeeType::Late, fees.get(asset.clone()).unwrap_or(0));
+        }
+        
+        Ok(results)
     }
+    
+    // === DISPUTES ===
 
-    // === LOAN MANAGEMENT ===
-    pub fn repay_bill(env: Env, bill_id: u64) {
+    /// Set the arbiter who rules on disputes (admin only)
+    pub fn set_arbiter(env: Env, admin: Address, arbiter: Address) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        
+        storage::set_arbiter(&env, &arbiter);
+        Ok(())
+    }
+    
+    pub fn get_arbiter(env: Env) -> Option<Address> {
+        storage::get_arbiter(&env)
+    }
+    
+    pub fn get_dispute(env: Env, bill_id: u64) -> Option<Dispute> {
+        storage::get_dispute(&env, bill_id)
+    }
+    
+    /// Dispute a paid bill (user only, once per bill)
+    /// Late fees and liquidation are frozen until the arbiter rules
+    pub fn open_dispute(env: Env, bill_id: u64, evidence_hash: BytesN<32>) {
         let mut bill = storage::get_bill(&env, bill_id);
         bill.user.require_auth();
-
-        let current_time = env.ledger().timestamp();
         
         if bill.status != BillStatus::Paid {
-            panic!("Bill not paid");
+            panic!("Bill not disputable");
+        }
+        if storage::get_dispute(&env, bill_id).is_some() {
+            panic!("Dispute already opened");
         }
         
-        let config = storage::get_config(&env);
-        let liquidity_pool_client = LPTokenClient::new(&env, &config.liquidity_pool);
-
-        let late_fee = Self::calc_late_fee(&env, bill.created_at, bill.principal);
-
-        // Transfer USDC from borrower
-        let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
-        usdc_client.transfer_from(&env.current_contract_address(), &bill.user, &env.current_contract_address(), &(&bill.principal + late_fee));
-
-        usdc_client.approve(&env.current_contract_address(), &config.liquidity_pool, &(&bill.principal + late_fee), &200);
-        liquidity_pool_client.repay(&env.current_contract_address(), &bill.principal);
-        Self::distribute_fees(env.clone(), late_fee);
-
-        bill.status = BillStatus::Repaid;
-
-        // Update loan
+        let dispute = Dispute {
+            bill_id,
+            evidence_hash: evidence_hash.clone(),
+            opened_at: env.ledger().timestamp(),
+            resolved_at: 0,
+            outcome: DisputeOutcome::Pending,
+        };
+        storage::set_dispute(&env, bill_id, &dispute);
+        
+        bill.status = BillStatus::Disputed;
         storage::set_bill(&env, bill_id, &bill);
+        Self::pause_interest(&env, &bill);
         
-        // Remove bill from user bills list after repayment
-        let mut user_bills = storage::get_user_bills(&env, &bill.user);
-        let mut new_user_bills = Vec::new(&env);
-        for i in 0..user_bills.len() {
-            let id = user_bills.get(i).unwrap();
-            if id != bill_id {
-                new_user_bills.push_back(id);
-            }
-        }
-        storage::set_user_bills(&env, &bill.user, &new_user_bills);
-
         env.events().publish(
-            (soroban_sdk::symbol_short!("repayment"), bill.user.clone(), bill_id),
-            RepaymentEvent {
+            (symbol_short!("disp_open"), bill.user.clone(), bill_id),
+            DisputeOpenedEvent {
                 bill_id,
                 user: bill.user,
-                amount_paid: bill.principal,
-                timestamp: env.ledger().timestamp(),
+                evidence_hash,
+                timestamp: dispute.opened_at,
             }
         );
     }
-
-    // === LIQUIDATION ===
-    pub fn liquidate_bill(
+    
+    /// Rule on an open dispute (arbiter only)
+    /// If the user wins, the user's debt is cleared and the principal is clawed back from the merchant's
+    /// reserve, then its bond (default asset only), and repaid to the pool. Any shortfall is recorded as
+    /// merchant debt and withheld from the merchant's later payouts
+    pub fn resolve_dispute(
         env: Env,
+        arbiter: Address,
         bill_id: u64,
-        liquidator: Address,
-    ) {
-        liquidator.require_auth();
-        
-        // Check if liquidator holds LP tokens
-        let config = storage::get_config(&env);
-        let lp_token_client = LPTokenClient::new(&env, &config.liquidity_pool);
-        let lp_balance = lp_token_client.balance(&liquidator);
+        outcome: DisputeOutcome,
+    ) -> Result<(), Error> {
+        arbiter.require_auth();
         
-        if lp_balance == 0 {
-            panic!("Not LP token holder");
+        if storage::get_arbiter(&env) != Some(arbiter.clone()) {
+            return Err(Error::NotArbiter);
         }
-
-        let mut bill = storage::get_bill(&env, bill_id);
         
-        if env.ledger().timestamp() < bill.created_at + (LIQUIDATION_THRESHOLD_DAYS * SECONDS_PER_DAY) {
-            panic!("Grace period not expired");
+        if outcome == DisputeOutcome::Pending {
+            return Err(Error::InvalidInput);
         }
         
-        if !(bill.status == BillStatus::Paid || bill.status == BillStatus::Overdue) {
-            panic!("Liquidation not possible");
-        }
-
-        bill.status = BillStatus::Liquidated;
-        storage::set_bill(&env, bill_id, &bill);
+        let mut bill = storage::get_bill(&env, bill_id);
+        let mut dispute = match storage::get_dispute(&env, bill_id) {
+            Some(dispute) if bill.status == BillStatus::Disputed => dispute,
+            _ => return Err(Error::DisputeNotOpen),
+        };
         
-        // Remove bill from user bills list after liquidation
-        let mut user_bills = storage::get_user_bills(&env, &bill.user);
-        let mut new_user_bills = Vec::new(&env);
-        for i in 0..user_bills.len() {
-            let id = user_bills.get(i).unwrap();
-            if id != bill_id {
-                new_user_bills.push_back(id);
+        dispute.resolved_at = env.ledger().timestamp();
+        dispute.outcome = outcome;
+        storage::set_dispute(&env, bill_id, &dispute);
+        
+        let mut clawed_back = 0;
+        let mut merchant_debt = 0;
+        if outcome == DisputeOutcome::MerchantWins {
+            bill.status = BillStatus::Paid;
+            Self::resume_interest(&env, &bill);
+        } else {
+            let liquidity_pool = Self::pool_for(&env, &bill.asset);
+            let liquidity_pool_client = LPTokenClient::new(&env, &liquidity_pool);
+            
+            // Merchant owes the full principal; the merchant fee is not refunded
+            clawed_back = Self::take_reserve(&env, &bill.merchant, &bill.asset, bill.principal);
+            if clawed_back < bill.principal && bill.asset == storage::get_config(&env).usdc_token {
+                let (from_bond, from_lp) = Self::take_bond(&env, &bill.merchant, bill.principal - clawed_back);
+                clawed_back += from_bond + from_lp;
+            }
+            if clawed_back > 0 {
+                Self::repay_pool(&env, &bill.asset, clawed_back);
+            }
+            
+            merchant_debt = bill.principal - clawed_back;
+            if merchant_debt > 0 {
+                let debt = storage::get_merchant_debt(&env, &bill.merchant, &bill.asset);
+                storage::set_merchant_debt(&env, &bill.merchant, &bill.asset, debt + merchant_debt);
             }
+            
+            Self::release_bill_collateral(&env, &liquidity_pool_client, &bill);
+            
+            Self::close_bill(&env, &bill);
+            
+            let mut stats = storage::get_merchant_stats(&env, &bill.merchant, &bill.asset);
+            stats.refunded += bill.principal;
+            storage::set_merchant_stats(&env, &bill.merchant, &bill.asset, &stats);
+            
+            bill.status = BillStatus::Refunded;
         }
-        storage::set_user_bills(&env, &bill.user, &new_user_bills);
-
-        let late_fee = Self::calc_late_fee(&env, bill.created_at, bill.principal);
-        let liquidation_fee = bill.principal * LIQUIDATION_PENALTY / SCALE_7;
-
-        let total_liquidated = bill.principal + late_fee + liquidation_fee;
-        
-        let config = storage::get_config(&env);
-        let liquidity_pool_client = LPTokenClient::new(&env, &config.liquidity_pool);
-        liquidity_pool_client.repay_with_burn(&bill.user, &bill.principal, &(late_fee+liquidation_fee));
-
-        let usdc_client = soroban_sdk::token::Client::new(&env, &config.usdc_token);
-        usdc_client.transfer(&env.current_contract_address(), &liquidator, &(liquidation_fee/2));
-        Self::distribute_fees(env.clone(), liquidation_fee/2 + late_fee);
+        storage::set_bill(&env, bill_id, &bill);
         
         env.events().publish(
-            (soroban_sdk::symbol_short!("liquidate"), liquidator.clone(), bill_id),
-            LiquidationEvent {
+            (symbol_short!("disp_res"), arbiter.clone(), bill_id),
+            DisputeResolvedEvent {
                 bill_id,
-                liquidator,
-                total_liquidated,
+                arbiter,
+                outcome,
+                clawed_back,
+                merchant_debt,
+                timestamp: dispute.resolved_at,
             }
         );
-    }
-
-    fn calc_late_fee(env: &Env, paid_date: u64, amount_paid: i128) -> i128 {
-        let current_time = env.ledger().timestamp();
-        let grace_period_seconds = GRACE_PERIOD_DAYS * SECONDS_PER_DAY;
         
-        // Only apply late fees after grace period
-        if current_time <= paid_date + grace_period_seconds {
-            return 0;
+        Ok(())
+    }
+    
+    /// Chargebacks a merchant still owes in `asset`, withheld from its next payouts
+    pub fn get_merchant_debt(env: Env, merchant: Address, asset: Address) -> i128 {
+        storage::get_merchant_debt(&env, &merchant, &asset)
+    }
+    
+    // internal function to withhold up to `available` of a payout against the merchant's debt
+    // The withheld amount is repaid to the pool; returns it
+    fn recover_merchant_debt(env: &Env, merchant: &Address, asset: &Address, available: i128) -> i128 {
+        let debt = storage::get_merchant_debt(env, merchant, asset);
+        let recovered = debt.min(available);
+        if recovered > 0 {
+            storage::set_merchant_debt(env, merchant, asset, debt - recovered);
+            Self::repay_pool(env, asset, recovered);
+        }
+        recovered
+    }
+    
+    // internal function to repay `amount` held by this contract to the pool of `asset`
+    fn repay_pool(env: &Env, asset: &Address, amount: i128) {
+        let liquidity_pool = Self::pool_for(env, asset);
+        let usdc_client = soroban_sdk::token::Client::new(env, asset);
+        usdc_client.approve(&env.current_contract_address(), &liquidity_pool, &amount, &env.ledger().sequence());
+        LPTokenClient::new(env, &liquidity_pool).repay(&env.current_contract_address(), &amount);
+    }
+    
+    // internal function to get the time a bill's clock was paused by a dispute
+    fn dispute_pause(env: &Env, bill_id: u64) -> u64 {
+        match storage::get_dispute(env, bill_id) {
+            Some(dispute) if dispute.resolved_at == 0 => env.ledger().timestamp() - dispute.opened_at,
+            Some(dispute) => dispute.resolved_at - dispute.opened_at,
+            None => 0,
         }
-        
-        // Calculate days overdue (after grace period)
-        let seconds_overdue = current_time - paid_date - grace_period_seconds;
-        let days_overdue = seconds_overdue / SECONDS_PER_DAY;
-        
-        // Apply late fee calculation
-        let late_fee = (amount_paid * LATE_INTEREST_APR * days_overdue as i128) / (365 * SCALE_7);
-        
-        late_fee
     }
 
     // === USER DASHBOARD ===
+
+    /// Debt in the default asset as (interest, principal)
     pub fn get_user_total_debt(env: Env, user: Address) -> (i128, i128) {
-        let user_bills = storage::get_user_bills(&env, &user);
-        let mut total_interest = 0i128;
-        let mut total_principal = 0i128;
+        let asset = storage::get_config(&env).usdc_token;
+        Self::get_user_pool_debt(env, user, asset)
+    }
+
+    /// Debt in `asset` as (interest, principal)
+    /// Both are running aggregates, so no bill is loaded: principal is summed as bills open and close, and
+    /// interest accrues at the combined late APR of the bills past grace, changing rate at scheduled grace
+    /// ends and liquidation deadlines. Interest is rounded once over the total, so it can exceed the sum of
+    /// per-bill late fees by less than one unit per open bill
+    pub fn get_user_pool_debt(env: Env, user: Address, asset: Address) -> (i128, i128) {
+        let total_principal = storage::get_user_principal(&env, &user, &asset);
         
-        for i in 0..user_bills.len() {
-            let bill_id = user_bills.get(i).unwrap();
-            let bill = storage::get_bill(&env, bill_id);
-            
-            if bill.status == BillStatus::Paid {
-                total_interest += Self::calc_late_fee(&env, bill.paid_at, bill.principal);
-                total_principal += bill.principal;
-            }
-        }
+        let mut accrual = storage::get_user_interest(&env, &user, &asset);
+        Self::accrue_interest(&mut accrual, env.ledger().timestamp());
         
-        (total_interest, total_principal)
+        (accrual.accrued / LATE_FEE_DENOMINATOR, total_principal)
     }
     
+    /// Collateral required in the default pool
     pub fn get_user_required_collateral(env: Env, user: Address) -> i128 {
-        let (total_interest, total_principal) = Self::get_user_total_debt(env.clone(), user.clone());
-        // let _config = storage::get_config(&env); // Unused variable
+        let asset = storage::get_config(&env).usdc_token;
+        Self::get_user_pool_collateral(env, user, asset)
+    }
+
+    /// Collateral required in the pool of `asset`
+    pub fn get_user_pool_collateral(env: Env, user: Address, asset: Address) -> i128 {
+        let (total_interest, total_principal) = Self::get_user_pool_debt(env.clone(), user.clone(), asset);
         
         // Calculate required collateral based on min_ltv (111%)
         (total_principal + total_interest) * COLLATERAL_RATIO / SCALE_7
     }
     
 
+    /// Borrowing power in the default pool
     pub fn get_user_borrowing_power(env: Env, user: Address) -> BorrowingPower {
-        let config = storage::get_config(&env);
-        let lp_client = LPTokenClient::new(&env, &config.liquidity_pool);
-        let lp_balance = lp_client.balance(&user);
+        let asset = storage::get_config(&env).usdc_token;
+        Self::get_user_pool_borrowing_power(env, user, asset)
+    }
+
+    /// Borrowing power in the pool of `asset`, backed only by LP tokens of that pool
+    /// LP locked elsewhere (e.g. as a merchant bond) doesn't count; LP locked for the user's own bills
+    /// does, since their debt is already subtracted
+    pub fn get_user_pool_borrowing_power(env: Env, user: Address, asset: Address) -> BorrowingPower {
+        let lp_client = LPTokenClient::new(&env, &Self::pool_for(&env, &asset));
+        let lp_balance = lp_client.available_balance(&user) + storage::get_user_bill_locks(&env, &user, &asset);
         
-        let (total_interest, total_principal) = Self::get_user_total_debt(env.clone(), user.clone());
+        let (total_interest, total_principal) = Self::get_user_pool_debt(env.clone(), user.clone(), asset);
         
         let max_borrowing = (lp_balance * 90) / 100;
         let available_borrowing = if max_borrowing > (total_principal + total_interest) {
@@ -510,7 +2369,306 @@ impl UnifiedBNPLContract {
     }
     
 
+    // === CROSS-ASSET COLLATERAL ===
+
+    /// Set the price oracle and maximum price age (admin only)
+    pub fn set_oracle(
+        env: Env,
+        admin: Address,
+        oracle: Address,
+        max_price_age: u64,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        
+        storage::set_oracle(&env, &OracleConfig { oracle, max_price_age });
+        Ok(())
+    }
+    
+    /// Set the collateral factor for an asset, scaled by 10^7 (admin only)
+    pub fn set_collateral_factor(
+        env: Env,
+        admin: Address,
+        asset: Address,
+        factor: i128,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        
+        if !Self::is_admin(env.clone(), admin) {
+            return Err(Error::NotAdmin);
+        }
+        
+        if !(0..=SCALE_7).contains(&factor) {
+            return Err(Error::InvalidInput);
+        }
+        
+        if storage::get_collateral_factor(&env, &asset).is_none() {
+            let mut assets = storage::get_collateral_assets(&env);
+            assets.push_back(asset.clone());
+            storage::set_collateral_assets(&env, &assets);
+        }
+        storage::set_collateral_factor(&env, &asset, factor);
+        Ok(())
+    }
+    
+    /// Get the collateral factor for an asset (0 if not accepted)
+    pub fn get_collateral_factor(env: Env, asset: Address) -> i128 {
+        storage::get_collateral_factor(&env, &asset).unwrap_or(0)
+    }
+    
+    /// Post collateral
+    pub fn deposit_collateral(env: Env, user: Address, asset: Address, amount: i128) {
+        user.require_auth();
+        
+        if storage::get_collateral_factor(&env, &asset).is_none() {
+            panic!("Unsupported collateral");
+        }
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        
+        let token_client = soroban_sdk::token::Client::new(&env, &asset);
+        token_client.transfer(&user, &env.current_contract_address(), &amount);
+        
+        let mut collateral = storage::get_user_collateral(&env, &user);
+        collateral.set(asset.clone(), collateral.get(asset.clone()).unwrap_or(0) + amount);
+        storage::set_user_collateral(&env, &user, &collateral);
+        
+        env.events().publish(
+            (symbol_short!("col_dep"), user.clone()),
+            CollateralEvent {
+                user,
+                asset,
+                amount,
+            }
+        );
+    }
+    
+    /// Withdraw posted collateral, as long as remaining debt stays covered
+    pub fn withdraw_collateral(env: Env, user: Address, asset: Address, amount: i128) {
+        user.require_auth();
+        
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        
+        let mut collateral = storage::get_user_collateral(&env, &user);
+        let balance = collateral.get(asset.clone()).unwrap_or(0);
+        if balance < amount {
+            panic!("Insufficient collateral");
+        }
+        
+        if balance == amount {
+            collateral.remove(asset.clone());
+        } else {
+            collateral.set(asset.clone(), balance - amount);
+        }
+        storage::set_user_collateral(&env, &user, &collateral);
+        
+        if !Self::collateral_covers(&env, &user, None) {
+            panic!("Insufficient collateral");
+        }
+        
+        let token_client = soroban_sdk::token::Client::new(&env, &asset);
+        token_client.transfer(&env.current_contract_address(), &user, &amount);
+        
+        env.events().publish(
+            (symbol_short!("col_wd"), user.clone()),
+            CollateralEvent {
+                user,
+                asset,
+                amount,
+            }
+        );
+    }
+    
+    /// Get posted collateral balances by asset
+    pub fn get_user_collateral(env: Env, user: Address) -> Map<Address, i128> {
+        storage::get_user_collateral(&env, &user)
+    }
+    
+    /// Collateral value after collateral factors, in oracle units
+    pub fn get_user_collateral_value(env: Env, user: Address) -> i128 {
+        let collateral = storage::get_user_collateral(&env, &user);
+        let mut total_value = 0i128;
+        
+        for (asset, amount) in collateral.iter() {
+            let factor = storage::get_collateral_factor(&env, &asset).unwrap_or(0);
+            total_value += Self::asset_value(&env, &asset, amount) * factor / SCALE_7;
+        }
+        
+        total_value
+    }
+    
+    /// Debt not covered by LP-based borrowing power, in oracle units
+    pub fn get_user_uncovered_debt(env: Env, user: Address) -> i128 {
+        Self::uncovered_debt_value(&env, &user, None)
+    }
+    
+    /// Whether posted collateral still covers the debt beyond LP-based borrowing power
+    pub fn is_collateral_healthy(env: Env, user: Address) -> bool {
+        Self::collateral_covers(&env, &user, None)
+    }
+    
+    /// Liquidate a bill of a user whose posted collateral no longer covers their debt
+    /// The liquidator repays the bill and receives collateral worth the repayment plus the liquidation penalty
+    pub fn liquidate_collateral(
+        env: Env,
+        bill_id: u64,
+        liquidator: Address,
+    ) {
+        liquidator.require_auth();
+        
+        let mut bill = storage::get_bill(&env, bill_id);
+        
+        if !(bill.status == BillStatus::Paid || bill.status == BillStatus::Overdue) {
+            panic!("Liquidation not possible");
+        }
+        if Self::collateral_covers(&env, &bill.user, None) {
+            panic!("Position healthy");
+        }
+        
+        // Liquidator repays the bill to the pool
+        let late_fee = Self::calc_late_fee(&env, &bill);
+        let repay_amount = bill.principal + late_fee;
+        let liquidity_pool = Self::pool_for(&env, &bill.asset);
+        
+        let usdc_client = soroban_sdk::token::Client::new(&env, &bill.asset);
+        usdc_client.transfer(&liquidator, &env.current_contract_address(), &repay_amount);
+        usdc_client.approve(&env.current_contract_address(), &liquidity_pool, &repay_amount, &env.ledger().sequence());
+        let lp_token_client = LPTokenClient::new(&env, &liquidity_pool);
+        lp_token_client.repay(&env.current_contract_address(), &bill.principal);
+        Self::release_bill_collateral(&env, &lp_token_client, &bill);
+        Self::distribute_fees(env.clone(), bill.asset.clone(), FeeType::Late, late_fee);
+        
+        bill.status = BillStatus::Liquidated;
+        storage::set_bill(&env, bill_id, &bill);
+        
+        // Remove bill from user bills list after liquidation
+        Self::close_bill(&env, &bill);
+        
+        // Seize collateral worth the repayment plus penalty
+        let mut remaining_value = Self::asset_value(&env, &bill.asset, repay_amount) * (SCALE_7 + LIQUIDATION_PENALTY) / SCALE_7;
+        let mut collateral = storage::get_user_collateral(&env, &bill.user);
+        let oracle_client = PriceOracleClient::new(&env, &Self::oracle_config(&env).oracle);
+        let unit = 10i128.pow(oracle_client.decimals());
+        
+        for asset in storage::get_collateral_assets(&env).iter() {
+            if remaining_value <= 0 {
+                break;
+            }
+            let balance = collateral.get(asset.clone()).unwrap_or(0);
+            if balance == 0 {
+                continue;
+            }
+            
+            let price = Self::oracle_price(&env, &asset);
+            let needed = (remaining_value * unit + price - 1) / price;
+            let seized = if needed < balance { needed } else { balance };
+            
+            if seized == balance {
+                collateral.remove(asset.clone());
+            } else {
+                collateral.set(asset.clone(), balance - seized);
+            }
+            remaining_value -= seized * price / unit;
+            
+            soroban_sdk::token::Client::new(&env, &asset).transfer(&env.current_contract_address(), &liquidator, &seized);
+        }
+        storage::set_user_collateral(&env, &bill.user, &collateral);
+        
+        env.events().publish(
+            (symbol_short!("col_liq"), liquidator.clone(), bill_id),
+            LiquidationEvent {
+                bill_id,
+                liquidator,
+                total_liquidated: repay_amount,
+            }
+        );
+    }
+    
+    // internal function to get the oracle configuration
+    fn oracle_config(env: &Env) -> OracleConfig {
+        match storage::get_oracle(env) {
+            Some(oracle) => oracle,
+            None => panic!("Oracle not set"),
+        }
+    }
+    
+    // internal function to fetch a fresh oracle price
+    fn oracle_price(env: &Env, asset: &Address) -> i128 {
+        let config = Self::oracle_config(env);
+        let oracle_client = PriceOracleClient::new(env, &config.oracle);
+        
+        let price_data = match oracle_client.lastprice(asset) {
+            Some(data) => data,
+            None => panic!("Price not available"),
+        };
+        if env.ledger().timestamp() > price_data.timestamp + config.max_price_age {
+            panic!("Stale price");
+        }
+        if price_data.price <= 0 {
+            panic!("Invalid price");
+        }
+        
+        price_data.price
+    }
+    
+    // internal function to value an amount of an asset in oracle units
+    fn asset_value(env: &Env, asset: &Address, amount: i128) -> i128 {
+        let decimals = PriceOracleClient::new(env, &Self::oracle_config(env).oracle).decimals();
+        amount * Self::oracle_price(env, asset) / 10i128.pow(decimals)
+    }
+    
+    // internal function to list debt beyond LP-based borrowing power per pool, in pool asset units
+    // `extra` adds prospective debt in one asset
+    fn uncovered_debts(env: &Env, user: &Address, extra: Option<(Address, i128)>) -> Map<Address, i128> {
+        let mut uncovered = Map::new(env);
+        
+        for asset in storage::get_pool_assets(env).iter() {
+            let power = Self::get_user_pool_borrowing_power(env.clone(), user.clone(), asset.clone());
+            let mut debt = power.current_debt;
+            if let Some((extra_asset, extra_amount)) = extra.clone() {
+                if extra_asset == asset {
+                    debt += extra_amount;
+                }
+            }
+            
+            if debt > power.max_borrowing {
+                uncovered.set(asset, debt - power.max_borrowing);
+            }
+        }
+        
+        uncovered
+    }
+    
+    // internal function to value uncovered debt in oracle units
+    fn uncovered_debt_value(env: &Env, user: &Address, extra: Option<(Address, i128)>) -> i128 {
+        let mut total_value = 0i128;
+        for (asset, amount) in Self::uncovered_debts(env, user, extra).iter() {
+            total_value += Self::asset_value(env, &asset, amount);
+        }
+        total_value
+    }
+    
+    // internal function to check posted collateral covers uncovered debt
+    fn collateral_covers(env: &Env, user: &Address, extra: Option<(Address, i128)>) -> bool {
+        // Skip the oracle entirely when no debt is uncovered or no collateral is posted
+        if Self::uncovered_debts(env, user, extra.clone()).is_empty() {
+            return true;
+        }
+        if storage::get_user_collateral(env, user).is_empty() {
+            return false;
+        }
+        
+        Self::uncovered_debt_value(env, user, extra) <= Self::get_user_collateral_value(env.clone(), user.clone())
+    }
+
     // === PROTOCOL CONSTANTS (Frontend) ===
+    // Fee splits are configurable per fee type; read them via get_fee_splits
     pub fn get_protocol_constants(env: Env) -> Map<String, i128> {
         let mut constants = Map::new(&env);
         constants.set(String::from_str(&env, "MERCHANT_FEE_RATE"), MERCHANT_FEE_RATE);
@@ -518,9 +2676,6 @@ impl UnifiedBNPLContract {
         constants.set(String::from_str(&env, "MAX_LTV"), MAX_LTV);
         constants.set(String::from_str(&env, "COLLATERAL_RATIO"), COLLATERAL_RATIO);
         constants.set(String::from_str(&env, "GRACE_PERIOD_DAYS"), GRACE_PERIOD_DAYS as i128);
-        constants.set(String::from_str(&env, "FEE_TO_LP_RATIO"), FEE_TO_LP_RATIO);
-        constants.set(String::from_str(&env, "FEE_TO_TREASURY_RATIO"), FEE_TO_TREASURY_RATIO);
-        constants.set(String::from_str(&env, "FEE_TO_INSURANCE_RATIO"), FEE_TO_INSURANCE_RATIO);
         constants
     }
 
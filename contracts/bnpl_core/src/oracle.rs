@@ -0,0 +1,16 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env};
+
+/// Latest price reported by an oracle
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,   // Price of one base unit of the asset, scaled by 10^decimals
+    pub timestamp: u64,
+}
+
+// Price oracle interface used to value collateral and debt
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleInterface {
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: Address) -> Option<PriceData>;
+}
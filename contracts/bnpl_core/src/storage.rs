@@ -1,5 +1,5 @@
-use soroban_sdk::{contracttype, Address, Env, Vec};
-use crate::types::{Config, Bill, MerchantData, MerchantStatus};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Map, String, Vec};
+use crate::types::{AutoRepayRecord, Config, Bill, BillTerm, Dispute, FeeType, InterestAccrual, MerchantBond, MerchantData, MerchantStats, MerchantStatus, OracleConfig, ReserveConfig, ReserveTranche};
 
 #[derive(Clone)]
 #[contracttype]
@@ -7,15 +7,85 @@ pub enum DataKey {
     // Configuration
     Config,
     
+    // Liquidity pools keyed by stablecoin asset
+    Pool(Address),
+    PoolAssets,
+    
+    // Fee ledgers
+    FeeSplits(FeeType),            // Recipients and ratios of a fee type
+    AccruedFees(Address, Address), // (recipient, asset) -> unclaimed fees
+    PendingLpFees(Address),        // asset -> LP fees waiting for a flush
+    
+    // Cross-asset collateral
+    Oracle,
+    CollateralFactor(Address),
+    CollateralAssets,
+    UserCollateral(Address),
+    
     // Simplified Merchant Data
     MerchantData(Address),
+    BillCreator(Address), // Sub-key -> merchant it creates bills for
 
     // Counters
     BillCounter,
     
     // Bills
     Bill(u64),
-    UserBills(Address),
+    BillLock(u64), // LP value locked for the bill in its pool
+    UserBillLocks(Address, Address), // LP value locked for open bills per (user, asset)
+    
+    // Per-user index of open bills (swap-remove array)
+    UserBillCount(Address),
+    UserBillAt(Address, u32),
+    BillPosition(u64),
+    
+    // Per-user history of every paid bill (append-only)
+    UserHistoryCount(Address),
+    UserHistoryAt(Address, u32),
+    
+    // Outstanding principal per (user, asset)
+    UserPrincipal(Address, Address),
+    
+    // Late interest accrual per (user, asset)
+    UserInterest(Address, Address),
+    
+    // Per-merchant index of every created bill (append-only)
+    MerchantBillCount(Address),
+    MerchantBillAt(Address, u32),
+    
+    // Latest bill per (merchant, order id)
+    OrderBill(Address, String),
+    
+    // Settlement totals per (merchant, asset)
+    MerchantStats(Address, Address),
+    
+    // Disputes
+    Arbiter,
+    Dispute(u64),
+    MerchantDebt(Address, Address), // Chargebacks not yet recovered per (merchant, asset)
+    
+    // Debt transfer offers
+    DebtTransfer(u64), // Proposed new debtor of a bill
+    
+    // Auto-repay
+    AutoRepay(Address),       // User's max repayment per bill
+    AutoRepayRecord(u64),     // Latest keeper attempt per bill
+    
+    // Signed checkout offers
+    CheckoutKey(Address),        // Merchant's ed25519 public key
+    CheckoutNonce(Address, u64), // Consumed offer nonces per merchant
+    
+    // Promotional terms offered by a merchant
+    MerchantTerms(Address),
+    
+    // Merchant bond and exposure
+    MerchantBond(Address),
+    MerchantExposureLimit(Address),
+    MerchantExposure(Address), // Outstanding principal of paid bills
+    
+    // Merchant rolling reserve
+    ReserveConfig(Address),
+    Reserve(Address, Address), // (merchant, asset) -> tranches ordered by release time
     
 }
 
@@ -33,6 +103,58 @@ pub fn set_config(env: &Env, config: &Config) {
     env.storage().instance().set(&DataKey::Config, config);
 }
 
+// === POOL FUNCTIONS ===
+
+pub fn get_pool(env: &Env, asset: &Address) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Pool(asset.clone()))
+}
+
+pub fn set_pool(env: &Env, asset: &Address, pool: &Address) {
+    env.storage().instance().set(&DataKey::Pool(asset.clone()), pool);
+}
+
+pub fn get_pool_assets(env: &Env) -> Vec<Address> {
+    env.storage().instance().get(&DataKey::PoolAssets).unwrap_or(Vec::new(env))
+}
+
+pub fn set_pool_assets(env: &Env, assets: &Vec<Address>) {
+    env.storage().instance().set(&DataKey::PoolAssets, assets);
+}
+
+// === COLLATERAL FUNCTIONS ===
+
+pub fn get_oracle(env: &Env) -> Option<OracleConfig> {
+    env.storage().instance().get(&DataKey::Oracle)
+}
+
+pub fn set_oracle(env: &Env, oracle: &OracleConfig) {
+    env.storage().instance().set(&DataKey::Oracle, oracle);
+}
+
+pub fn get_collateral_factor(env: &Env, asset: &Address) -> Option<i128> {
+    env.storage().instance().get(&DataKey::CollateralFactor(asset.clone()))
+}
+
+pub fn set_collateral_factor(env: &Env, asset: &Address, factor: i128) {
+    env.storage().instance().set(&DataKey::CollateralFactor(asset.clone()), &factor);
+}
+
+pub fn get_collateral_assets(env: &Env) -> Vec<Address> {
+    env.storage().instance().get(&DataKey::CollateralAssets).unwrap_or(Vec::new(env))
+}
+
+pub fn set_collateral_assets(env: &Env, assets: &Vec<Address>) {
+    env.storage().instance().set(&DataKey::CollateralAssets, assets);
+}
+
+pub fn get_user_collateral(env: &Env, user: &Address) -> Map<Address, i128> {
+    env.storage().persistent().get(&DataKey::UserCollateral(user.clone())).unwrap_or(Map::new(env))
+}
+
+pub fn set_user_collateral(env: &Env, user: &Address, collateral: &Map<Address, i128>) {
+    env.storage().persistent().set(&DataKey::UserCollateral(user.clone()), collateral);
+}
+
 // === MERCHANT STATUS FUNCTIONS ===
 
 pub fn get_merchant_data(env: &Env, merchant: &Address) -> Option<MerchantData> {
@@ -47,6 +169,18 @@ pub fn is_merchant_approved(env: &Env, merchant: &Address) -> bool {
     get_merchant_data(env, merchant).is_some() && get_merchant_data(env, merchant).unwrap().status == MerchantStatus::Approved
 }
 
+pub fn get_bill_creator_merchant(env: &Env, creator: &Address) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::BillCreator(creator.clone()))
+}
+
+pub fn set_bill_creator_merchant(env: &Env, creator: &Address, merchant: &Address) {
+    env.storage().persistent().set(&DataKey::BillCreator(creator.clone()), merchant);
+}
+
+pub fn remove_bill_creator_merchant(env: &Env, creator: &Address) {
+    env.storage().persistent().remove(&DataKey::BillCreator(creator.clone()));
+}
+
 // === COUNTER FUNCTIONS ===
 
 pub fn get_bill_counter(env: &Env) -> u64 {
@@ -71,11 +205,302 @@ pub fn has_bill(env: &Env, bill_id: u64) -> bool {
     env.storage().persistent().has(&DataKey::Bill(bill_id))
 }
 
-pub fn get_user_bills(env: &Env, user: &Address) -> Vec<u64> {
-    env.storage().persistent().get(&DataKey::UserBills(user.clone())).unwrap_or(Vec::new(env))
+pub fn get_bill_lock(env: &Env, bill_id: u64) -> i128 {
+    env.storage().persistent().get(&DataKey::BillLock(bill_id)).unwrap_or(0)
+}
+
+pub fn set_bill_lock(env: &Env, bill_id: u64, amount: i128) {
+    env.storage().persistent().set(&DataKey::BillLock(bill_id), &amount);
+}
+
+pub fn remove_bill_lock(env: &Env, bill_id: u64) {
+    env.storage().persistent().remove(&DataKey::BillLock(bill_id));
+}
+
+pub fn get_user_bill_locks(env: &Env, user: &Address, asset: &Address) -> i128 {
+    env.storage().persistent().get(&DataKey::UserBillLocks(user.clone(), asset.clone())).unwrap_or(0)
+}
+
+pub fn set_user_bill_locks(env: &Env, user: &Address, asset: &Address, amount: i128) {
+    env.storage().persistent().set(&DataKey::UserBillLocks(user.clone(), asset.clone()), &amount);
+}
+
+// === USER BILL INDEX FUNCTIONS ===
+
+pub fn get_user_bill_count(env: &Env, user: &Address) -> u32 {
+    env.storage().persistent().get(&DataKey::UserBillCount(user.clone())).unwrap_or(0)
+}
+
+pub fn get_user_bill_at(env: &Env, user: &Address, index: u32) -> u64 {
+    env.storage().persistent().get(&DataKey::UserBillAt(user.clone(), index)).unwrap()
+}
+
+pub fn get_user_history_count(env: &Env, user: &Address) -> u32 {
+    env.storage().persistent().get(&DataKey::UserHistoryCount(user.clone())).unwrap_or(0)
+}
+
+pub fn get_user_history_at(env: &Env, user: &Address, index: u32) -> u64 {
+    env.storage().persistent().get(&DataKey::UserHistoryAt(user.clone(), index)).unwrap()
+}
+
+pub fn get_user_principal(env: &Env, user: &Address, asset: &Address) -> i128 {
+    env.storage().persistent().get(&DataKey::UserPrincipal(user.clone(), asset.clone())).unwrap_or(0)
+}
+
+fn set_user_principal(env: &Env, user: &Address, asset: &Address, principal: i128) {
+    env.storage().persistent().set(&DataKey::UserPrincipal(user.clone(), asset.clone()), &principal);
+}
+
+pub fn get_user_interest(env: &Env, user: &Address, asset: &Address) -> InterestAccrual {
+    env.storage().persistent().get(&DataKey::UserInterest(user.clone(), asset.clone())).unwrap_or(InterestAccrual {
+        accrued: 0,
+        rate: 0,
+        updated_at: 0,
+        schedule: Vec::new(env),
+    })
+}
+
+pub fn set_user_interest(env: &Env, user: &Address, asset: &Address, accrual: &InterestAccrual) {
+    env.storage().persistent().set(&DataKey::UserInterest(user.clone(), asset.clone()), accrual);
+}
+
+/// Add a paid bill to its user's open index and history
+pub fn add_user_bill(env: &Env, bill: &Bill) {
+    let count = get_user_bill_count(env, &bill.user);
+    env.storage().persistent().set(&DataKey::UserBillAt(bill.user.clone(), count), &bill.id);
+    env.storage().persistent().set(&DataKey::BillPosition(bill.id), &count);
+    env.storage().persistent().set(&DataKey::UserBillCount(bill.user.clone()), &(count + 1));
+    
+    let history_count = get_user_history_count(env, &bill.user);
+    env.storage().persistent().set(&DataKey::UserHistoryAt(bill.user.clone(), history_count), &bill.id);
+    env.storage().persistent().set(&DataKey::UserHistoryCount(bill.user.clone()), &(history_count + 1));
+    
+    let principal = get_user_principal(env, &bill.user, &bill.asset);
+    set_user_principal(env, &bill.user, &bill.asset, principal + bill.principal);
+}
+
+/// Remove a bill from its user's open index by moving the last entry into its slot
+pub fn remove_user_bill(env: &Env, bill: &Bill) {
+    let position: u32 = env.storage().persistent().get(&DataKey::BillPosition(bill.id)).unwrap();
+    let last = get_user_bill_count(env, &bill.user) - 1;
+    
+    if position != last {
+        let last_id = get_user_bill_at(env, &bill.user, last);
+        env.storage().persistent().set(&DataKey::UserBillAt(bill.user.clone(), position), &last_id);
+        env.storage().persistent().set(&DataKey::BillPosition(last_id), &position);
+    }
+    env.storage().persistent().remove(&DataKey::UserBillAt(bill.user.clone(), last));
+    env.storage().persistent().remove(&DataKey::BillPosition(bill.id));
+    env.storage().persistent().set(&DataKey::UserBillCount(bill.user.clone()), &last);
+    
+    let principal = get_user_principal(env, &bill.user, &bill.asset);
+    set_user_principal(env, &bill.user, &bill.asset, principal - bill.principal);
 }
 
-pub fn set_user_bills(env: &Env, user: &Address, bills: &Vec<u64>) {
-    env.storage().persistent().set(&DataKey::UserBills(user.clone()), bills);
+
+// === MERCHANT BILL INDEX FUNCTIONS ===
+
+pub fn get_order_bill(env: &Env, merchant: &Address, order_id: &String) -> Option<u64> {
+    env.storage().persistent().get(&DataKey::OrderBill(merchant.clone(), order_id.clone()))
 }
 
+pub fn set_order_bill(env: &Env, merchant: &Address, order_id: &String, bill_id: u64) {
+    env.storage().persistent().set(&DataKey::OrderBill(merchant.clone(), order_id.clone()), &bill_id);
+}
+
+pub fn get_merchant_bill_count(env: &Env, merchant: &Address) -> u32 {
+    env.storage().persistent().get(&DataKey::MerchantBillCount(merchant.clone())).unwrap_or(0)
+}
+
+pub fn get_merchant_bill_at(env: &Env, merchant: &Address, index: u32) -> u64 {
+    env.storage().persistent().get(&DataKey::MerchantBillAt(merchant.clone(), index)).unwrap()
+}
+
+pub fn add_merchant_bill(env: &Env, merchant: &Address, bill_id: u64) {
+    let count = get_merchant_bill_count(env, merchant);
+    env.storage().persistent().set(&DataKey::MerchantBillAt(merchant.clone(), count), &bill_id);
+    env.storage().persistent().set(&DataKey::MerchantBillCount(merchant.clone()), &(count + 1));
+}
+
+pub fn get_merchant_stats(env: &Env, merchant: &Address, asset: &Address) -> MerchantStats {
+    env.storage().persistent().get(&DataKey::MerchantStats(merchant.clone(), asset.clone())).unwrap_or(MerchantStats {
+        bills_created: 0,
+        bills_paid: 0,
+        financed_volume: 0,
+        fees_paid: 0,
+        refunded: 0,
+    })
+}
+
+pub fn set_merchant_stats(env: &Env, merchant: &Address, asset: &Address, stats: &MerchantStats) {
+    env.storage().persistent().set(&DataKey::MerchantStats(merchant.clone(), asset.clone()), stats);
+}
+
+// === DISPUTE FUNCTIONS ===
+
+pub fn get_arbiter(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Arbiter)
+}
+
+pub fn set_arbiter(env: &Env, arbiter: &Address) {
+    env.storage().instance().set(&DataKey::Arbiter, arbiter);
+}
+
+pub fn get_dispute(env: &Env, bill_id: u64) -> Option<Dispute> {
+    env.storage().persistent().get(&DataKey::Dispute(bill_id))
+}
+
+pub fn set_dispute(env: &Env, bill_id: u64, dispute: &Dispute) {
+    env.storage().persistent().set(&DataKey::Dispute(bill_id), dispute);
+}
+
+pub fn get_merchant_debt(env: &Env, merchant: &Address, asset: &Address) -> i128 {
+    env.storage().persistent().get(&DataKey::MerchantDebt(merchant.clone(), asset.clone())).unwrap_or(0)
+}
+
+pub fn set_merchant_debt(env: &Env, merchant: &Address, asset: &Address, debt: i128) {
+    env.storage().persistent().set(&DataKey::MerchantDebt(merchant.clone(), asset.clone()), &debt);
+}
+
+// === MERCHANT RESERVE FUNCTIONS ===
+
+pub fn get_reserve_config(env: &Env, merchant: &Address) -> Option<ReserveConfig> {
+    env.storage().persistent().get(&DataKey::ReserveConfig(merchant.clone()))
+}
+
+pub fn set_reserve_config(env: &Env, merchant: &Address, config: &ReserveConfig) {
+    env.storage().persistent().set(&DataKey::ReserveConfig(merchant.clone()), config);
+}
+
+pub fn get_reserve(env: &Env, merchant: &Address, asset: &Address) -> Vec<ReserveTranche> {
+    env.storage().persistent().get(&DataKey::Reserve(merchant.clone(), asset.clone())).unwrap_or(Vec::new(env))
+}
+
+pub fn set_reserve(env: &Env, merchant: &Address, asset: &Address, tranches: &Vec<ReserveTranche>) {
+    env.storage().persistent().set(&DataKey::Reserve(merchant.clone(), asset.clone()), tranches);
+}
+
+// === MERCHANT BOND FUNCTIONS ===
+
+pub fn get_merchant_bond(env: &Env, merchant: &Address) -> MerchantBond {
+    env.storage().persistent().get(&DataKey::MerchantBond(merchant.clone())).unwrap_or(MerchantBond {
+        amount: 0,
+        lp_locked: 0,
+    })
+}
+
+pub fn set_merchant_bond(env: &Env, merchant: &Address, bond: &MerchantBond) {
+    env.storage().persistent().set(&DataKey::MerchantBond(merchant.clone()), bond);
+}
+
+pub fn get_merchant_exposure_limit(env: &Env, merchant: &Address) -> Option<i128> {
+    env.storage().persistent().get(&DataKey::MerchantExposureLimit(merchant.clone()))
+}
+
+pub fn set_merchant_exposure_limit(env: &Env, merchant: &Address, limit: i128) {
+    env.storage().persistent().set(&DataKey::MerchantExposureLimit(merchant.clone()), &limit);
+}
+
+pub fn get_merchant_exposure(env: &Env, merchant: &Address) -> i128 {
+    env.storage().persistent().get(&DataKey::MerchantExposure(merchant.clone())).unwrap_or(0)
+}
+
+pub fn set_merchant_exposure(env: &Env, merchant: &Address, exposure: i128) {
+    env.storage().persistent().set(&DataKey::MerchantExposure(merchant.clone()), &exposure);
+}
+
+// === MERCHANT TERM FUNCTIONS ===
+
+pub fn get_merchant_terms(env: &Env, merchant: &Address) -> Vec<BillTerm> {
+    env.storage().persistent().get(&DataKey::MerchantTerms(merchant.clone())).unwrap_or(Vec::new(env))
+}
+
+pub fn set_merchant_terms(env: &Env, merchant: &Address, terms: &Vec<BillTerm>) {
+    env.storage().persistent().set(&DataKey::MerchantTerms(merchant.clone()), terms);
+}
+
+// === CHECKOUT FUNCTIONS ===
+
+pub fn get_checkout_key(env: &Env, merchant: &Address) -> Option<BytesN<32>> {
+    env.storage().persistent().get(&DataKey::CheckoutKey(merchant.clone()))
+}
+
+pub fn set_checkout_key(env: &Env, merchant: &Address, public_key: &BytesN<32>) {
+    env.storage().persistent().set(&DataKey::CheckoutKey(merchant.clone()), public_key);
+}
+
+pub fn is_nonce_used(env: &Env, merchant: &Address, nonce: u64) -> bool {
+    env.storage().persistent().has(&DataKey::CheckoutNonce(merchant.clone(), nonce))
+}
+
+pub fn use_nonce(env: &Env, merchant: &Address, nonce: u64) {
+    env.storage().persistent().set(&DataKey::CheckoutNonce(merchant.clone(), nonce), &true);
+}
+
+// === AUTO-REPAY FUNCTIONS ===
+
+pub fn get_auto_repay(env: &Env, user: &Address) -> Option<i128> {
+    env.storage().persistent().get(&DataKey::AutoRepay(user.clone()))
+}
+
+pub fn set_auto_repay(env: &Env, user: &Address, max_amount: i128) {
+    env.storage().persistent().set(&DataKey::AutoRepay(user.clone()), &max_amount);
+}
+
+pub fn remove_auto_repay(env: &Env, user: &Address) {
+    env.storage().persistent().remove(&DataKey::AutoRepay(user.clone()));
+}
+
+pub fn get_auto_repay_record(env: &Env, bill_id: u64) -> Option<AutoRepayRecord> {
+    env.storage().persistent().get(&DataKey::AutoRepayRecord(bill_id))
+}
+
+pub fn set_auto_repay_record(env: &Env, bill_id: u64, record: &AutoRepayRecord) {
+    env.storage().persistent().set(&DataKey::AutoRepayRecord(bill_id), record);
+}
+
+// === DEBT TRANSFER FUNCTIONS ===
+
+pub fn get_debt_transfer(env: &Env, bill_id: u64) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::DebtTransfer(bill_id))
+}
+
+pub fn set_debt_transfer(env: &Env, bill_id: u64, new_user: &Address) {
+    env.storage().persistent().set(&DataKey::DebtTransfer(bill_id), new_user);
+}
+
+pub fn remove_debt_transfer(env: &Env, bill_id: u64) {
+    env.storage().persistent().remove(&DataKey::DebtTransfer(bill_id));
+}
+
+// === FEE LEDGER FUNCTIONS ===
+
+pub fn get_accrued_fees(env: &Env, recipient: &Address, asset: &Address) -> i128 {
+    env.storage().persistent().get(&DataKey::AccruedFees(recipient.clone(), asset.clone())).unwrap_or(0)
+}
+
+pub fn set_accrued_fees(env: &Env, recipient: &Address, asset: &Address, amount: i128) {
+    env.storage().persistent().set(&DataKey::AccruedFees(recipient.clone(), asset.clone()), &amount);
+}
+
+pub fn add_accrued_fees(env: &Env, recipient: &Address, asset: &Address, amount: i128) {
+    if amount > 0 {
+        set_accrued_fees(env, recipient, asset, get_accrued_fees(env, recipient, asset) + amount);
+    }
+}
+
+pub fn get_pending_lp_fees(env: &Env, asset: &Address) -> i128 {
+    env.storage().instance().get(&DataKey::PendingLpFees(asset.clone())).unwrap_or(0)
+}
+
+pub fn set_pending_lp_fees(env: &Env, asset: &Address, amount: i128) {
+    env.storage().instance().set(&DataKey::PendingLpFees(asset.clone()), &amount);
+}
+
+pub fn get_fee_splits(env: &Env, fee_type: &FeeType) -> Option<Vec<(Address, i128)>> {
+    env.storage().instance().get(&DataKey::FeeSplits(fee_type.clone()))
+}
+
+pub fn set_fee_splits(env: &Env, fee_type: &FeeType, splits: &Vec<(Address, i128)>) {
+    env.storage().instance().set(&DataKey::FeeSplits(fee_type.clone()), splits);
+}
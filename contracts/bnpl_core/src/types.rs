@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, String, Map};
+use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
 
 // === CORE DATA STRUCTURES ===
 
@@ -14,11 +14,62 @@ pub struct Config {
     pub insurance_fund: Address,   // New field - Optional for backward compatibility
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub max_price_age: u64, // Seconds before a price is considered stale
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct MerchantData {
     pub merchant_info_id: String,  // MongoDB merchant info ID
     pub status: MerchantStatus,
+    pub payout_address: Option<Address>,  // Receives payouts instead of the merchant key
+    pub pending_payout: Option<Address>,  // Requested payout address, applied once the delay has passed
+    pub payout_effective_at: u64,
+    pub bill_creators: Vec<Address>,      // Sub-keys allowed to create bills
+    pub status_reason: u32,               // Reason code of the last status change (0 = unspecified)
+    pub status_updated_at: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct MerchantStats {
+    pub bills_created: u32,
+    pub bills_paid: u32,
+    pub financed_volume: i128, // Principal of paid bills
+    pub fees_paid: i128,       // Merchant fees withheld
+    pub refunded: i128,        // Amount refunded to users
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct MerchantBond {
+    pub amount: i128,    // Default-asset bond held by this contract
+    pub lp_locked: i128, // Default-pool LP value locked in place
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct ReserveConfig {
+    pub rate: i128,     // Share of each payout held back (scaled by 10^7)
+    pub hold_days: u64, // Days before held funds are releasable
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct ReserveTranche {
+    pub amount: i128,
+    pub release_at: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct ReserveBalance {
+    pub pending: i128,    // Still within the holding period
+    pub releasable: i128, // Can be released now
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -38,11 +89,97 @@ pub struct Bill {
     pub id: u64,
     pub merchant: Address,
     pub user: Address,
+    pub asset: Address, // Stablecoin the bill is denominated in
     pub principal: i128,
     pub status: BillStatus,
     pub order_id: String,
     pub created_at: u64,
     pub paid_at: u64,
+    pub metadata_hash: Option<BytesN<32>>, // sha256 of the off-chain receipt (e.g. line items)
+    pub display_currency: Option<String>,  // Currency code shown to the user, e.g. "EUR"
+    pub memo: Option<String>,
+    pub term: BillTerm, // Repayment terms fixed at creation
+}
+
+/// Optional data attached to a bill at creation
+#[derive(Clone)]
+#[contracttype]
+pub struct BillMetadata {
+    pub metadata_hash: Option<BytesN<32>>,
+    pub display_currency: Option<String>,
+    pub memo: Option<String>,
+    pub term: Option<u32>, // Index into the merchant's term menu, None for the standard term
+}
+
+/// Fees with their own configurable split between recipients
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub enum FeeType {
+    Merchant,
+    Late,
+    Liquidation,
+}
+
+/// One bill of a `create_bills` batch
+#[derive(Clone)]
+#[contracttype]
+pub struct BillRequest {
+    pub user: Address,
+    pub asset: Address,
+    pub amount: i128,
+    pub order_id: String,
+    pub term: Option<u32>,
+}
+
+/// Outcome of one item of a batch call; `error` is the skipped item's `Error` code, 0 on success
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct BatchResult {
+    pub bill_id: u64,
+    pub error: u32,
+}
+
+/// Bill offer signed off-chain by the merchant's checkout key and submitted by the user
+#[derive(Clone)]
+#[contracttype]
+pub struct BillOffer {
+    pub merchant: Address,
+    pub user: Address,    // Only this user can accept the offer
+    pub asset: Address,
+    pub amount: i128,
+    pub order_id: String,
+    pub expiry: u64,      // Last timestamp the offer can be accepted
+    pub nonce: u64,       // Unique per merchant, consumed on checkout
+    pub term: Option<u32>,
+}
+
+/// Result of a keeper's auto-repay attempt
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub enum AutoRepayStatus {
+    Repaid,
+    OverMaxAmount,
+    InsufficientAllowance,
+    InsufficientBalance,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct AutoRepayRecord {
+    pub status: AutoRepayStatus,
+    pub amount: i128, // Repayment including late fee and keeper tip
+    pub keeper: Address,
+    pub timestamp: u64,
+}
+
+/// Repayment terms of a bill; promotional terms trade a higher merchant fee for longer grace
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct BillTerm {
+    pub fee_rate: i128,        // Merchant fee (scaled by 10^7)
+    pub grace_days: u64,       // Days before late interest accrues
+    pub liquidation_days: u64, // Days before the bill can be liquidated
+    pub late_apr: i128,        // Late interest APR (scaled by 10^7)
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -55,8 +192,54 @@ pub enum BillStatus {
     Repaid, // Repaid
     Overdue, // Overdue -- late fee applied
     Liquidated, // Liquidated -- liquidation fee applied
+    Cancelled, // Cancelled by the merchant before payment
+    Disputed, // Under dispute -- late fees and liquidation frozen
+    Refunded, // Dispute ruled for the user -- merchant payout clawed back
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[contracttype]
+pub enum DisputeOutcome {
+    Pending,      // Not yet ruled on
+    UserWins,     // Merchant payout clawed back, debt cleared
+    MerchantWins, // Bill resumes as paid
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct Dispute {
+    pub bill_id: u64,
+    pub evidence_hash: BytesN<32>,
+    pub opened_at: u64,
+    pub resolved_at: u64, // 0 while open
+    pub outcome: DisputeOutcome,
+}
+
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BillPage {
+    pub bills: Vec<Bill>,
+    pub next_cursor: u32, // Resume scanning here; done once it reaches the bill count
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct RateChange {
+    pub at: u64,
+    pub bill_id: u64,
+    pub delta: i128, // Change to the accrual rate (principal * late APR)
 }
 
+// Late interest per (user, asset), in principal * late APR * seconds
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct InterestAccrual {
+    pub accrued: i128,             // Accrued as of `updated_at`
+    pub rate: i128,                // Sum of principal * late APR over bills accruing now
+    pub updated_at: u64,
+    pub schedule: Vec<RateChange>, // Upcoming grace ends and liquidation deadlines, soonest first
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -80,12 +263,29 @@ pub struct MerchantEnrolledEvent {
 }
 
 // Event types
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct PayoutAddressRequestedEvent {
+    pub merchant: Address,
+    pub payout_address: Address,
+    pub effective_at: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct BillCreatorUpdatedEvent {
+    pub merchant: Address,
+    pub creator: Address,
+    pub authorized: bool,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 #[contracttype]
 pub struct MerchantStatusUpdatedEvent {
     pub merchant: Address,
     pub old_status: MerchantStatus,
     pub new_status: MerchantStatus,
+    pub reason: u32,
     pub timestamp: u64,
 }
 
@@ -114,9 +314,113 @@ pub struct BillCreatedEvent {
     pub bill_id: u64,
     pub merchant: Address,
     pub user: Address,
+    pub asset: Address,
     pub amount: i128,
     pub order_id: String,
     pub created_at: u64,
+    pub metadata_hash: Option<BytesN<32>>,
+    pub display_currency: Option<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct DebtTransferEvent {
+    pub bill_id: u64,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct AutoRepayEvent {
+    pub bill_id: u64,
+    pub keeper: Address,
+    pub status: AutoRepayStatus,
+    pub amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct CollateralEvent {
+    pub user: Address,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct PoolRegisteredEvent {
+    pub asset: Address,
+    pub pool: Address,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct BillCancelledEvent {
+    pub bill_id: u64,
+    pub merchant: Address,
+    pub order_id: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct DisputeOpenedEvent {
+    pub bill_id: u64,
+    pub user: Address,
+    pub evidence_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct DisputeResolvedEvent {
+    pub bill_id: u64,
+    pub arbiter: Address,
+    pub outcome: DisputeOutcome,
+    pub clawed_back: i128,
+    pub merchant_debt: i128, // Part of the chargeback left to recover from future payouts
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct BondEvent {
+    pub merchant: Address,
+    pub amount: i128,
+    pub lp_amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct FeeSplitEvent {
+    pub fee_type: FeeType,
+    pub recipient: Address,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct FeesFlushedEvent {
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct FeesClaimedEvent {
+    pub recipient: Address,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[contracttype]
+pub struct ReserveReleasedEvent {
+    pub merchant: Address,
+    pub asset: Address,
+    pub amount: i128,
 }
 
 #[derive(Clone, PartialEq, Debug)]
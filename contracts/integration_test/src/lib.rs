@@ -4,12 +4,12 @@
 use soroban_sdk::{
     testutils::{Address as _, Ledger}, 
     token::TokenClient,
-    Env, Address, String
+    Env, Address, BytesN, String
 };
 use soroban_token_sdk::metadata::TokenMetadata;
 
 // Import types from interface
-use soroban_sdk::{contract, contracttype};
+use soroban_sdk::contracttype;
 
 // We need to define the types that were previously imported from other crates
 #[derive(Clone, PartialEq, Debug)]
@@ -47,6 +47,12 @@ pub struct Config {
 pub struct MerchantData {
     pub merchant_info_id: String,
     pub status: MerchantStatus,
+    pub payout_address: Option<Address>,
+    pub pending_payout: Option<Address>,
+    pub payout_effective_at: u64,
+    pub bill_creators: soroban_sdk::Vec<Address>,
+    pub status_reason: u32,
+    pub status_updated_at: u64,
 }
 
 #[derive(Clone)]
@@ -55,25 +61,25 @@ pub struct Bill {
     pub id: u64,
     pub merchant: Address,
     pub user: Address,
+    pub asset: Address,
     pub order_id: String,
     pub principal: i128,
     pub status: BillStatus,
     pub created_at: u64,
     pub paid_at: u64,
+    pub metadata_hash: Option<BytesN<32>>,
+    pub display_currency: Option<String>,
+    pub memo: Option<String>,
+    pub term: BillTerm,
 }
 
-// Mock contracts for testing
-mod mock_contracts {
-    use super::*;
-    
-    #[contract]
-    pub struct MockBnplCore;
-    
-    #[contract]
-    pub struct MockUsdcToken;
-    
-    #[contract]
-    pub struct MockLpToken;
+#[derive(Clone)]
+#[contracttype]
+pub struct BillTerm {
+    pub fee_rate: i128,
+    pub grace_days: u64,
+    pub liquidation_days: u64,
+    pub late_apr: i128,
 }
 
 // Constants from bnpl_core
@@ -94,13 +100,18 @@ pub trait UnifiedBNPLContractTrait {
     fn initialize(env: Env, liquidity_pool: Address, usdc_token: Address, admin: Address, treasury: Address, insurance_fund: Address);
     fn get_config(env: Env) -> Config;
     fn enroll_merchant(env: Env, merchant: Address, merchant_info_id: String);
-    fn update_merchant_status(env: Env, admin: Address, merchant: Address, new_status: MerchantStatus);
+    fn update_merchant_status(env: Env, admin: Address, merchant: Address, new_status: MerchantStatus, reason: u32);
+    fn set_merchant_exposure_limit(env: Env, admin: Address, merchant: Address, limit: i128);
+    fn post_bond(env: Env, merchant: Address, amount: i128);
     fn get_merchant(env: Env, merchant: Address) -> MerchantData;
-    fn create_bill(env: Env, merchant: Address, user: Address, amount: i128, order_id: String) -> u64;
+    fn set_merchant_terms(env: Env, admin: Address, merchant: Address, terms: soroban_sdk::Vec<BillTerm>);
+    fn create_bill(env: Env, merchant: Address, user: Address, amount: i128, order_id: String, term: Option<u32>) -> u64;
     fn get_bill(env: Env, bill_id: u64) -> Bill;
     fn pay_bill_bnpl(env: Env, bill_id: u64);
     fn repay_bill(env: Env, bill_id: u64);
     fn liquidate_bill(env: Env, bill_id: u64, liquidator: Address);
+    fn flush_fees(env: Env);
+    fn claim_fees(env: Env, recipient: Address) -> soroban_sdk::Map<Address, i128>;
 }
 
 #[contractclient(name = "UsdcTokenClient")]
@@ -118,6 +129,7 @@ pub trait LpTokenTrait {
     fn deposit(env: Env, from: Address, amount: i128) -> i128;
     fn withdraw(env: Env, from: Address, lp_amount: i128) -> i128;
     fn balance(env: Env, address: Address) -> i128;
+    fn available_balance(env: Env, user: Address) -> i128;
 }
 
 fn create_test_env() -> (Env, Address, Address, Address, Address, Address) {
@@ -163,6 +175,16 @@ fn create_test_env() -> (Env, Address, Address, Address, Address, Address) {
     (env, bnpl_core_id, lp_token_id, usdc_token_id, treasury, insurance_fund)
 }
 
+// Approve a merchant with a 10,000 USDC exposure limit backed by a posted bond
+fn approve_merchant(bnpl_client: &UnifiedBNPLContractClient, usdc_client: &UsdcTokenClient, admin: &Address, merchant: &Address) {
+    let limit = 10_000_000_000;
+    let bond = limit / 10;
+    bnpl_client.set_merchant_exposure_limit(admin, merchant, &limit);
+    usdc_client.mint(merchant, &bond);
+    bnpl_client.post_bond(merchant, &bond);
+    bnpl_client.update_merchant_status(admin, merchant, &MerchantStatus::Approved, &0);
+}
+
 #[test]
 fn test_initialization() {
     let (env, bnpl_core, lp_token, usdc_token_id, treasury, insurance_fund) = create_test_env();
@@ -227,8 +249,8 @@ fn test_basic_bnpl_scenario() {
     usdc_client.approve(&user, &lp_token, &2_000_000_000, &200);
     lp_client.deposit(&user, &2_000_000_000);
     
-    // Verify LP tokens were minted
-    assert_eq!(lp_client.balance(&lp_provider), 10_000_000_000);
+    // Verify LP tokens were minted (first deposit locks 1,000 units of minimum liquidity)
+    assert_eq!(lp_client.balance(&lp_provider), 10_000_000_000 - 1_000);
     assert_eq!(lp_client.balance(&user), 2_000_000_000);
     
     // Step 4: Enroll merchant
@@ -236,7 +258,7 @@ fn test_basic_bnpl_scenario() {
     bnpl_client.enroll_merchant(&merchant, &merchant_info_id);
     
     // Step 5: Admin approves merchant
-    bnpl_client.update_merchant_status(&admin, &merchant, &MerchantStatus::Approved);
+    approve_merchant(&bnpl_client, &usdc_client, &admin, &merchant);
     
     // Verify merchant status
     let merchant_data = bnpl_client.get_merchant(&merchant);
@@ -250,6 +272,7 @@ fn test_basic_bnpl_scenario() {
         &user,
         &purchase_amount,
         &order_id,
+        &None,
     );
     
     // Verify bill was created
@@ -276,6 +299,8 @@ fn test_basic_bnpl_scenario() {
     let treasury_fee = (merchant_fee * 2_000_000) / SCALE_7; // 20% to treasury
     let insurance_fee = (merchant_fee * 1_000_000) / SCALE_7; // 10% to insurance
     
+    bnpl_client.claim_fees(&treasury);
+    bnpl_client.claim_fees(&insurance_fund);
     assert_eq!(token_client.balance(&treasury), treasury_fee);
     assert_eq!(token_client.balance(&insurance_fund), insurance_fee);
     
@@ -290,7 +315,8 @@ fn test_basic_bnpl_scenario() {
     // Verify user's balance decreased (initial 5,000 - 2,000 deposited - 1,000 repaid)
     assert_eq!(token_client.balance(&user), 5_000_000_000 - 2_000_000_000 - purchase_amount);
     
-    // Step 9: LP provider can withdraw their funds plus earned fees
+    // Step 9: LP provider can withdraw their funds plus earned fees once they are flushed
+    bnpl_client.flush_fees();
     let lp_balance = lp_client.balance(&lp_provider);
     lp_client.withdraw(&lp_provider, &lp_balance);
     
@@ -339,7 +365,7 @@ fn test_liquidation_scenario() {
     // Step 4: Setup merchant
     let merchant_info_id = String::from_str(&env, "MERCHANT_002");
     bnpl_client.enroll_merchant(&merchant, &merchant_info_id);
-    bnpl_client.update_merchant_status(&admin, &merchant, &MerchantStatus::Approved);
+    approve_merchant(&bnpl_client, &usdc_client, &admin, &merchant);
     
     // Step 5: Create and pay a bill
     let purchase_amount = 2_000_000_000; // 2,000 USDC
@@ -349,6 +375,7 @@ fn test_liquidation_scenario() {
         &user,
         &purchase_amount,
         &order_id,
+        &None,
     );
     
     // Pay the bill (BNPL pays merchant)
@@ -360,7 +387,7 @@ fn test_liquidation_scenario() {
     
     // Step 6: Move time forward past the liquidation threshold (29 days)
     env.ledger().with_mut(|li| {
-        li.timestamp = li.timestamp + (29 * SECONDS_PER_DAY); // 29 days later (past 28 day threshold)
+        li.timestamp += 29 * SECONDS_PER_DAY; // 29 days later (past 28 day threshold)
     });
     
     // Step 7: Liquidate the bill
@@ -387,6 +414,65 @@ fn test_liquidation_scenario() {
     assert_eq!(liquidator_final_balance, liquidator_initial_balance + liquidator_reward);
     
     // Treasury and insurance fund should receive their share of fees
+    bnpl_client.claim_fees(&treasury);
+    bnpl_client.claim_fees(&insurance_fund);
     assert!(token_client.balance(&treasury) > 0);
     assert!(token_client.balance(&insurance_fund) > 0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_liquidation_covered_by_term_sized_lock() {
+    let (env, bnpl_core, lp_token, usdc_token, treasury, insurance_fund) = create_test_env();
+    let admin = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+    let lp_provider = Address::generate(&env);
+    
+    let bnpl_client = UnifiedBNPLContractClient::new(&env, &bnpl_core);
+    let usdc_client = UsdcTokenClient::new(&env, &usdc_token);
+    let lp_client = LpTokenClient::new(&env, &lp_token);
+    
+    bnpl_client.initialize(&lp_token, &usdc_token, &admin, &treasury, &insurance_fund);
+    
+    usdc_client.mint(&lp_provider, &10_000_000_000);
+    usdc_client.mint(&user, &3_000_000_000);
+    usdc_client.mint(&liquidator, &1_000_000_000);
+    
+    usdc_client.approve(&lp_provider, &lp_token, &10_000_000_000, &200);
+    lp_client.deposit(&lp_provider, &10_000_000_000);
+    usdc_client.approve(&user, &lp_token, &3_000_000_000, &200);
+    lp_client.deposit(&user, &3_000_000_000);
+    usdc_client.approve(&liquidator, &lp_token, &1_000_000_000, &200);
+    lp_client.deposit(&liquidator, &1_000_000_000);
+    
+    bnpl_client.enroll_merchant(&merchant, &String::from_str(&env, "MERCHANT_003"));
+    approve_merchant(&bnpl_client, &usdc_client, &admin, &merchant);
+    
+    // 100% late APR from day one, liquidatable after 60 days
+    let term = BillTerm { fee_rate: MERCHANT_FEE_RATE, grace_days: 0, liquidation_days: 60, late_apr: 10_000_000 };
+    bnpl_client.set_merchant_terms(&admin, &merchant, &soroban_sdk::vec![&env, term]);
+    
+    let purchase_amount = 2_000_000_000;
+    let bill_id = bnpl_client.create_bill(&merchant, &user, &purchase_amount, &String::from_str(&env, "ORDER_003"), &Some(0));
+    bnpl_client.pay_bill_bnpl(&bill_id);
+    
+    // The lock covers 60 days of late interest plus the liquidation penalty, well above 111%
+    let max_late_fee = purchase_amount * 60 / 365;
+    let liquidation_fee = purchase_amount * LIQUIDATION_PENALTY / SCALE_7;
+    let max_debt = purchase_amount + max_late_fee + liquidation_fee;
+    assert!(max_debt > purchase_amount * 111 / 100);
+    assert_eq!(lp_client.available_balance(&user), 3_000_000_000 - max_debt);
+    
+    // The user pulls out everything that isn't locked
+    lp_client.withdraw(&user, &lp_client.available_balance(&user));
+    
+    // Long past the deadline the debt is still covered by what is left
+    env.ledger().with_mut(|li| {
+        li.timestamp += 90 * SECONDS_PER_DAY;
+    });
+    bnpl_client.liquidate_bill(&bill_id, &liquidator);
+    
+    assert_eq!(bnpl_client.get_bill(&bill_id).status, BillStatus::Liquidated);
+    assert!(lp_client.balance(&user) <= 1);
+}
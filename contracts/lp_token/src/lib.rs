@@ -1,8 +1,7 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Map};
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Map, Vec};
 use soroban_sdk::token::{TokenInterface, TokenClient};
 use soroban_token_sdk::metadata::TokenMetadata;
-use bnpl_core_interface::BnplCoreClient;
 
 // === EVENT TYPES ===
 #[contracttype]
@@ -43,7 +42,79 @@ pub struct LiquidationBurnEvent {
     pub fee: i128,
 }
 
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RedeemRepayEvent {
+    pub user: Address,
+    pub amount: i128,
+    pub fee: i128,
+    pub shares_burned: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SlashEvent {
+    pub user: Address,
+    pub amount: i128,
+    pub shares_burned: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct MintEvent {
+    pub admin: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub shares_minted: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct YieldEvent {
+    pub from: Address,
+    pub amount: i128,
+    pub new_index: u128,
+    pub vesting_end: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct CollateralLockEvent {
+    pub user: Address,
+    pub amount: i128,
+    pub locked: i128,
+}
+
+/// Yield received but not yet reflected in the index, released linearly until `end`
+/// A new distribution restarts the window for the whole unvested amount
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct VestingState {
+    pub unvested: u128,
+    pub last_update: u64,
+    pub end: u64,
+}
+
+/// Point-in-time record of the pool used for historical APY
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct IndexSnapshot {
+    pub timestamp: u64,
+    pub index: u128,
+    pub total_assets: i128,
+    pub total_borrowed: u128,
+}
+
 const DECIMALS: u128 = 1_000_000_000; // 1e9 for precision
+// Raw shares locked forever on the first deposit so the supply can never be
+// drained back to a dust amount that lets the index be inflated cheaply
+const MINIMUM_LIQUIDITY: u128 = 1_000;
+
+// Index snapshots (ring buffer)
+const MAX_SNAPSHOTS: u32 = 64;
+const SECONDS_PER_DAY: u64 = 86_400;
+const SECONDS_PER_YEAR: u64 = 365 * SECONDS_PER_DAY;
+const DEFAULT_SNAPSHOT_INTERVAL: u64 = SECONDS_PER_DAY;
 
 #[contract]
 pub struct LpToken;
@@ -76,6 +147,108 @@ impl LpToken {
         env.storage().instance().set(&symbol_short!("index"), &index);
     }
 
+    /// Underlying tokens accounted to the pool (idle liquidity, excluding borrowed)
+    /// Direct transfers to the contract are not counted, so they can't move the index
+    fn get_cash(env: &Env) -> u128 {
+        env.storage().instance().get(&symbol_short!("cash")).unwrap_or(0)
+    }
+
+    fn set_cash(env: &Env, cash: u128) {
+        env.storage().instance().set(&symbol_short!("cash"), &cash);
+    }
+
+    fn get_vesting(env: &Env) -> VestingState {
+        env.storage().instance().get(&symbol_short!("vesting")).unwrap_or(VestingState {
+            unvested: 0,
+            last_update: 0,
+            end: 0,
+        })
+    }
+
+    fn set_vesting(env: &Env, state: &VestingState) {
+        env.storage().instance().set(&symbol_short!("vesting"), state);
+    }
+
+    /// Portion of the unvested yield released between the last update and `timestamp`
+    fn vested_amount(state: &VestingState, timestamp: u64) -> u128 {
+        if state.unvested == 0 || timestamp <= state.last_update {
+            return 0;
+        }
+        if timestamp >= state.end {
+            return state.unvested;
+        }
+        state.unvested * ((timestamp - state.last_update) as u128) / ((state.end - state.last_update) as u128)
+    }
+
+    /// Release vested yield up to now, returning what is still unvested
+    fn accrue_vesting(env: &Env) -> u128 {
+        let mut state = Self::get_vesting(env);
+        let now = env.ledger().timestamp();
+        if now <= state.last_update {
+            return state.unvested;
+        }
+
+        state.unvested -= Self::vested_amount(&state, now);
+        state.last_update = now;
+        Self::set_vesting(env, &state);
+        state.unvested
+    }
+
+    /// Index implied by `total_assets`; the index never decreases
+    fn compute_index(env: &Env, total_assets: i128) -> u128 {
+        let current_index = Self::get_index(env);
+        let supply = Self::get_supply(env);
+        if total_assets <= 0 || supply == 0 {
+            return current_index;
+        }
+
+        let expected_value = (supply * current_index / DECIMALS) as i128;
+        if total_assets > expected_value {
+            (total_assets as u128) * DECIMALS / supply
+        } else {
+            current_index
+        }
+    }
+
+    fn get_snapshot_buffer(env: &Env) -> Vec<IndexSnapshot> {
+        env.storage().instance().get(&symbol_short!("snaps")).unwrap_or(Vec::new(env))
+    }
+
+    /// Slot the next snapshot overwrites once the buffer is full (also the oldest entry)
+    fn get_snapshot_head(env: &Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("snap_head")).unwrap_or(0)
+    }
+
+    /// Write a snapshot if at least one interval has passed since the latest one
+    fn record_snapshot(env: &Env, total_assets: i128) {
+        let now = env.ledger().timestamp();
+        let mut snapshots = Self::get_snapshot_buffer(env);
+        let head = Self::get_snapshot_head(env);
+
+        if !snapshots.is_empty() {
+            let latest_slot = if snapshots.len() < MAX_SNAPSHOTS { snapshots.len() - 1 } else { (head + MAX_SNAPSHOTS - 1) % MAX_SNAPSHOTS };
+            let latest = snapshots.get(latest_slot).unwrap();
+            if now < latest.timestamp + Self::snapshot_interval(env.clone()) {
+                return;
+            }
+        }
+
+        let snapshot = IndexSnapshot {
+            timestamp: now,
+            index: Self::get_index(env),
+            total_assets,
+            total_borrowed: Self::total_borrowed(env.clone()),
+        };
+
+        if snapshots.len() < MAX_SNAPSHOTS {
+            snapshots.push_back(snapshot);
+        } else {
+            snapshots.set(head, snapshot);
+            env.storage().instance().set(&symbol_short!("snap_head"), &((head + 1) % MAX_SNAPSHOTS));
+        }
+        env.storage().instance().set(&symbol_short!("snaps"), &snapshots);
+    }
+
     /// Get raw shares for a user (internal use)
     fn get_shares(env: &Env, user: &Address) -> u128 {
         Self::get_balances(env).get(user.clone()).unwrap_or(0)
@@ -88,29 +261,124 @@ impl LpToken {
         Self::set_balances(env, balances);
     }
 
-    /// Convert USDC amount to shares at current index
+    /// Convert USDC amount to shares at current index (rounds down, used when minting)
     fn amount_to_shares(env: &Env, amount: u128) -> u128 {
         let index = Self::get_index(env);
         amount * DECIMALS / index
     }
 
+    /// Convert USDC amount to shares at current index (rounds up, used when burning or
+    /// moving shares so a holder can never take out more value than their shares cover)
+    fn amount_to_shares_up(env: &Env, amount: u128) -> u128 {
+        let index = Self::get_index(env);
+        (amount * DECIMALS).div_ceil(index)
+    }
+
     /// Convert shares to USDC amount at current index
     fn shares_to_amount(env: &Env, shares: u128) -> u128 {
         let index = Self::get_index(env);
         shares * index / DECIMALS
     }
 
+    fn get_account_caps(env: &Env) -> Map<Address, i128> {
+        env.storage().instance().get(&symbol_short!("acct_cap")).unwrap_or(Map::new(env))
+    }
+
+    fn get_allowlist(env: &Env) -> Map<Address, bool> {
+        env.storage().instance().get(&symbol_short!("allowlist")).unwrap_or(Map::new(env))
+    }
+
+    /// Check that `to` may hold `new_shares` raw shares under the allowlist and account caps
+    fn check_recipient(env: &Env, to: &Address, new_shares: u128) {
+        if Self::allowlist_enabled(env.clone()) {
+            assert!(Self::get_allowlist(env).get(to.clone()).unwrap_or(false), "account not allowlisted");
+        }
+
+        if let Some(cap) = Self::get_account_caps(env).get(to.clone()) {
+            assert!(Self::shares_to_amount(env, new_shares) as i128 <= cap, "account cap exceeded");
+        }
+    }
+
+    /// Pull `amount` of underlying from `payer` and mint the matching shares to `to`
+    /// The first issuance into an empty pool permanently locks `MINIMUM_LIQUIDITY` shares
+    /// Returns the raw shares credited to `to`
+    fn issue_shares(env: &Env, payer: &Address, to: &Address, amount: i128) -> u128 {
+        assert!(amount > 0, "invalid amount");
+
+        // Calculate shares to mint based on current index
+        let shares_to_mint = Self::amount_to_shares(env, amount as u128);
+        assert!(shares_to_mint > 0, "deposit too small");
+
+        // First issuance locks MINIMUM_LIQUIDITY shares with the contract itself
+        let supply = Self::get_supply(env);
+        let shares_credited = if supply == 0 {
+            assert!(shares_to_mint > MINIMUM_LIQUIDITY, "deposit too small");
+            Self::set_shares(env, &env.current_contract_address(), MINIMUM_LIQUIDITY);
+            shares_to_mint - MINIMUM_LIQUIDITY
+        } else {
+            shares_to_mint
+        };
+
+        // Respect the supply cap (USDC value), if one is set
+        if let Some(cap) = env.storage().instance().get::<_, i128>(&symbol_short!("cap")) {
+            let new_supply_value = Self::shares_to_amount(env, supply + shares_to_mint) as i128;
+            assert!(new_supply_value <= cap, "supply cap exceeded");
+        }
+
+        // Transfer underlying tokens from payer to this contract
+        let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
+        let underlying_client = TokenClient::new(env, &underlying_asset);
+        underlying_client.transfer(payer, &env.current_contract_address(), &amount);
+        Self::set_cash(env, Self::get_cash(env) + amount as u128);
+
+        // Update recipient's shares
+        let current_shares = Self::get_shares(env, to);
+        Self::check_recipient(env, to, current_shares + shares_credited);
+        Self::set_shares(env, to, current_shares + shares_credited);
+
+        // Update total supply
+        Self::set_supply(env, supply + shares_to_mint);
+
+        shares_credited
+    }
+
+    /// Burn `amount` worth of shares and drop the same amount from accounted cash
+    ///
+    /// The underlying stays in the contract as an unaccounted donation, so a plain
+    /// burn forfeits value without raising the index for the remaining holders
+    fn burn_forfeit(env: &Env, from: &Address, amount: i128) {
+        // Update index first so the burn is priced at the current rate
+        Self::update_index(env.clone());
+
+        let available = Self::available_balance(env.clone(), from.clone());
+        assert!(amount <= available, "insufficient available balance");
+
+        let cash = Self::get_cash(env);
+        assert!(amount as u128 <= cash, "insufficient liquidity");
+
+        let shares_to_burn = Self::amount_to_shares_up(env, amount as u128);
+        let user_shares = Self::get_shares(env, from);
+        assert!(shares_to_burn <= user_shares, "insufficient balance");
+
+        Self::set_shares(env, from, user_shares - shares_to_burn);
+
+        let current_supply = Self::get_supply(env);
+        Self::set_supply(env, current_supply - shares_to_burn);
+        Self::set_cash(env, cash - amount as u128);
+    }
+
     fn do_transfer(env: Env, from: Address, to: Address, amount: i128) {
         // Check available balance
         let available = Self::available_balance(env.clone(), from.clone());
         assert!(amount <= available, "insufficient available balance");
 
-        let shares_to_transfer = Self::amount_to_shares(&env, amount as u128);
+        let shares_to_transfer = Self::amount_to_shares_up(&env, amount as u128);
 
         let from_shares = Self::get_shares(&env, &from);
         let to_shares = Self::get_shares(&env, &to);
 
         assert!(shares_to_transfer <= from_shares, "insufficient balance");
+        Self::check_recipient(&env, &to, to_shares + shares_to_transfer);
 
         Self::set_shares(&env, &from, from_shares - shares_to_transfer);
         Self::set_shares(&env, &to, to_shares + shares_to_transfer);
@@ -119,68 +387,189 @@ impl LpToken {
     // ==================== Public Functions ====================
 
     pub fn initialize(env: Env, admin: Address, underlying_asset: Address, metadata: TokenMetadata) {
+        if env.storage().instance().has(&symbol_short!("admin")) {
+            panic!("Already initialized");
+        }
         admin.require_auth();
         env.storage().instance().set(&symbol_short!("admin"), &admin);
         env.storage().instance().set(&symbol_short!("asset"), &underlying_asset);
         env.storage().instance().set(&symbol_short!("metadata"), &metadata);
         env.storage().instance().set(&symbol_short!("index"), &DECIMALS); // Start at 1.0
         env.storage().instance().set(&symbol_short!("supply"), &0u128);
+        env.storage().instance().set(&symbol_short!("cash"), &0u128);
         env.storage().instance().set(&symbol_short!("balances"), &Map::<Address, u128>::new(&env));
         env.storage().instance().set(&symbol_short!("allowance"), &Map::<(Address, Address), u128>::new(&env));
         env.storage().instance().set(&symbol_short!("borrowed"), &0u128);
     }
 
-    /// Update the index based on current underlying balance vs LP supply
-    /// This distributes any excess underlying tokens to all LP holders proportionally
+    /// Update the index based on accounted assets (cash + borrowed - unvested yield) vs LP supply
+    /// This distributes any accounted excess to all LP holders proportionally
     ///
-    /// Call this after sending tokens to the contract to distribute them as yield
+    /// Tokens sent directly to the contract are ignored; yield must come in through
+    /// `distribute_yield` so it can't be used to inflate the index
     pub fn update_index(env: Env) {
-        let underlying_balance = Self::total_underlying(env.clone());
+        let unvested = Self::accrue_vesting(&env) as i128;
+        let cash = Self::total_underlying(env.clone());
+        let total_borrowed = Self::total_borrowed(env.clone()) as i128;
+        let total_assets = cash + total_borrowed - unvested;
+
+        let new_index = Self::compute_index(&env, total_assets);
+        if new_index != Self::get_index(&env) {
+            Self::set_index(&env, new_index);
+        }
+
+        Self::record_snapshot(&env, total_assets);
+    }
+
+    /// Index the pool would have at `timestamp` if only the current unvested yield is released
+    pub fn projected_index(env: Env, timestamp: u64) -> u128 {
+        let state = Self::get_vesting(&env);
+        let unvested = state.unvested - Self::vested_amount(&state, timestamp);
+        let cash = Self::total_underlying(env.clone());
         let total_borrowed = Self::total_borrowed(env.clone()) as i128;
-        let total_assets = underlying_balance + total_borrowed;
 
-        if total_assets <= 0 {
-            return;
+        Self::compute_index(&env, cash + total_borrowed - unvested as i128)
+    }
+
+    /// Yield received but not yet released into the index
+    pub fn unvested_rewards(env: Env) -> i128 {
+        let state = Self::get_vesting(&env);
+        (state.unvested - Self::vested_amount(&state, env.ledger().timestamp())) as i128
+    }
+
+    /// Set the window (in seconds) over which new yield is streamed into the index (admin only)
+    /// 0 releases yield immediately
+    pub fn set_vesting_period(env: Env, period: u64) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&symbol_short!("vest_per"), &period);
+    }
+
+    /// Get the yield vesting window in seconds
+    pub fn vesting_period(env: Env) -> u64 {
+        env.storage().instance().get(&symbol_short!("vest_per")).unwrap_or(0)
+    }
+
+    /// Set the minimum time (in seconds) between index snapshots (admin only)
+    pub fn set_snapshot_interval(env: Env, interval: u64) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&symbol_short!("snap_int"), &interval);
+    }
+
+    /// Get the minimum time between index snapshots in seconds
+    pub fn snapshot_interval(env: Env) -> u64 {
+        env.storage().instance().get(&symbol_short!("snap_int")).unwrap_or(DEFAULT_SNAPSHOT_INTERVAL)
+    }
+
+    /// Get the stored index snapshots, oldest first (at most `MAX_SNAPSHOTS`)
+    pub fn get_snapshots(env: Env) -> Vec<IndexSnapshot> {
+        let snapshots = Self::get_snapshot_buffer(&env);
+        if snapshots.len() < MAX_SNAPSHOTS {
+            return snapshots;
         }
 
-        let supply = Self::get_supply(&env);
-        if supply == 0 {
-            return;
+        let head = Self::get_snapshot_head(&env);
+        let mut ordered = Vec::new(&env);
+        for i in 0..MAX_SNAPSHOTS {
+            ordered.push_back(snapshots.get((head + i) % MAX_SNAPSHOTS).unwrap());
         }
+        ordered
+    }
 
-        let current_index = Self::get_index(&env);
-        let expected_value = (supply * current_index / DECIMALS) as i128;
+    /// Annualized (simple, non-compounded) index growth over the trailing `window` seconds
+    /// Uses the latest snapshot at or before `now - window`, or the oldest one if history is shorter
+    /// Returns basis points (10000 = 100%), saturating at u32::MAX
+    pub fn trailing_apy(env: Env, window: u64) -> u32 {
+        let snapshots = Self::get_snapshots(env.clone());
+        if snapshots.is_empty() {
+            return 0;
+        }
 
-        // If we have more assets than expected, increase the index
-        if total_assets > expected_value {
-            let new_index = (total_assets as u128) * DECIMALS / supply;
-            Self::set_index(&env, new_index);
+        let now = env.ledger().timestamp();
+        let target = now.saturating_sub(window);
+        let mut base = snapshots.get(0).unwrap();
+        for snapshot in snapshots.iter() {
+            if snapshot.timestamp > target {
+                break;
+            }
+            base = snapshot;
+        }
+
+        let elapsed = now - base.timestamp;
+        let current_index = Self::projected_index(env.clone(), now);
+        if elapsed == 0 || current_index <= base.index {
+            return 0;
+        }
+
+        let apy = (current_index - base.index) * 10000 * SECONDS_PER_YEAR as u128 / (base.index * elapsed as u128);
+        apy.min(u32::MAX as u128) as u32
+    }
+
+    /// Trailing 7-day APY in basis points
+    pub fn apy_7d(env: Env) -> u32 {
+        Self::trailing_apy(env, 7 * SECONDS_PER_DAY)
+    }
+
+    /// Trailing 30-day APY in basis points
+    pub fn apy_30d(env: Env) -> u32 {
+        Self::trailing_apy(env, 30 * SECONDS_PER_DAY)
+    }
+
+    /// Pull yield from `from` into the pool and distribute it to LP holders (BNPL Core only)
+    /// `from` must have approved this contract for `amount` of the underlying asset
+    ///
+    /// The stream restarts on every call: whatever was still unvested is merged with the new
+    /// yield and released over a fresh `vesting_period`. Keeping each payout on its own end
+    /// time would need an unbounded list of streams, so pending yield can be pushed back by
+    /// up to one window when payouts overlap; nothing is lost, only released later
+    pub fn distribute_yield(env: Env, from: Address, amount: i128) {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+        assert!(amount > 0, "invalid amount");
+
+        let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
+        let underlying_client = TokenClient::new(&env, &underlying_asset);
+        underlying_client.transfer_from(&env.current_contract_address(), &from, &env.current_contract_address(), &amount);
+
+        // Release whatever has vested so far, then restart the stream with the new yield on top
+        Self::update_index(env.clone());
+
+        let now = env.ledger().timestamp();
+        let period = Self::vesting_period(env.clone());
+        let mut state = Self::get_vesting(&env);
+        if period > 0 {
+            state.unvested += amount as u128;
+            state.last_update = now;
+            state.end = now + period;
+            Self::set_vesting(&env, &state);
         }
+
+        Self::set_cash(&env, Self::get_cash(&env) + amount as u128);
+        Self::update_index(env.clone());
+
+        env.events().publish(
+            (symbol_short!("yield"), from.clone()),
+            YieldEvent {
+                from,
+                amount,
+                new_index: Self::get_index(&env),
+                vesting_end: state.end.max(now),
+            }
+        );
     }
 
     /// Deposit underlying assets and receive LP tokens
     /// Returns the amount of LP tokens (USDC value) credited to the user
+    ///
+    /// The first deposit into an empty pool permanently locks `MINIMUM_LIQUIDITY` shares
     pub fn deposit(env: Env, from: Address, amount: i128) -> i128 {
         from.require_auth();
 
         // Update index first to ensure fair exchange rate
         Self::update_index(env.clone());
 
-        // Transfer underlying tokens from user to this contract
-        let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
-        let underlying_client = TokenClient::new(&env, &underlying_asset);
-        underlying_client.transfer(&from, &env.current_contract_address(), &amount);
-
-        // Calculate shares to mint based on current index
-        let shares_to_mint = Self::amount_to_shares(&env, amount as u128);
-
-        // Update user's shares
-        let current_shares = Self::get_shares(&env, &from);
-        Self::set_shares(&env, &from, current_shares + shares_to_mint);
-
-        // Update total supply
-        let new_supply = Self::get_supply(&env) + shares_to_mint;
-        Self::set_supply(&env, new_supply);
+        let shares_minted = Self::issue_shares(&env, &from, &from, amount);
 
         // Emit deposit event
         env.events().publish(
@@ -188,12 +577,12 @@ impl LpToken {
             DepositEvent {
                 user: from,
                 amount,
-                shares_minted: shares_to_mint,
+                shares_minted,
             }
         );
 
-        // Return the USDC value deposited (which equals amount)
-        amount
+        // Return the USDC value credited to the user
+        Self::shares_to_amount(&env, shares_minted) as i128
     }
 
     /// Withdraw LP tokens and receive underlying assets
@@ -202,12 +591,18 @@ impl LpToken {
     pub fn withdraw(env: Env, from: Address, amount: i128) -> i128 {
         from.require_auth();
 
+        // Update index first so vested yield is included
+        Self::update_index(env.clone());
+
         // Check available balance
         let available = Self::available_balance(env.clone(), from.clone());
         assert!(amount <= available, "insufficient available balance");
 
+        let cash = Self::get_cash(&env);
+        assert!(amount as u128 <= cash, "insufficient liquidity");
+
         // Calculate shares to burn
-        let shares_to_burn = Self::amount_to_shares(&env, amount as u128);
+        let shares_to_burn = Self::amount_to_shares_up(&env, amount as u128);
 
         let user_shares = Self::get_shares(&env, &from);
         assert!(shares_to_burn <= user_shares, "insufficient balance");
@@ -220,6 +615,7 @@ impl LpToken {
         Self::set_supply(&env, current_supply - shares_to_burn);
 
         // Transfer underlying tokens back to user
+        Self::set_cash(&env, cash - amount as u128);
         let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
         let underlying_client = TokenClient::new(&env, &underlying_asset);
         underlying_client.transfer(&env.current_contract_address(), &from, &amount);
@@ -248,11 +644,10 @@ impl LpToken {
         env.storage().instance().get(&symbol_short!("asset")).unwrap()
     }
 
-    /// Get total underlying assets held by the contract
+    /// Get total underlying assets accounted to the pool (idle liquidity, excluding borrowed)
+    /// Tokens transferred to the contract outside of deposit/repay/yield are not included
     pub fn total_underlying(env: Env) -> i128 {
-        let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
-        let underlying_client = TokenClient::new(&env, &underlying_asset);
-        underlying_client.balance(&env.current_contract_address())
+        Self::get_cash(&env) as i128
     }
 
     /// Set the BNPL Core contract address (admin only)
@@ -272,6 +667,10 @@ impl LpToken {
         let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
         bnpl_core.require_auth();
 
+        let cash = Self::get_cash(&env);
+        assert!(amount as u128 <= cash, "insufficient liquidity");
+        Self::set_cash(&env, cash - amount as u128);
+
         let current_borrowed: u128 = env.storage().instance().get(&symbol_short!("borrowed")).unwrap_or(0);
         env.storage().instance().set(&symbol_short!("borrowed"), &(current_borrowed + (amount as u128)));
 
@@ -301,6 +700,7 @@ impl LpToken {
         };
 
         env.storage().instance().set(&symbol_short!("borrowed"), &(current_borrowed - repay_amount));
+        Self::set_cash(&env, Self::get_cash(&env) + repay_amount);
 
         let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
         let underlying_client = TokenClient::new(&env, &underlying_asset);
@@ -325,43 +725,142 @@ impl LpToken {
         let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
         bnpl_core.require_auth();
 
+        Self::update_index(env.clone());
+
         let total_to_burn = amount + fee;
-        let shares_to_burn = Self::amount_to_shares(&env, total_to_burn as u128);
+        let shares_to_burn = Self::amount_to_shares_up(&env, total_to_burn as u128);
 
         let user_shares = Self::get_shares(&env, &from);
         assert!(shares_to_burn <= user_shares, "insufficient balance for liquidation");
 
+        Self::burn_for_debt(&env, &bnpl_core, &from, shares_to_burn, amount, fee);
+
+        env.events().publish(
+            (symbol_short!("liq_burn"), from.clone()),
+            LiquidationBurnEvent {
+                user: from,
+                amount_burned: total_to_burn,
+                fee,
+            }
+        );
+    }
+
+    /// Repay with burn for a batch of liquidations as (user, amount, fee) (BNPL Core only)
+    /// The index is updated once for the whole batch
+    pub fn repay_with_burn_batch(env: Env, burns: Vec<(Address, i128, i128)>) {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+
+        Self::update_index(env.clone());
+
+        for (from, amount, fee) in burns.iter() {
+            let total_to_burn = amount + fee;
+            let shares_to_burn = Self::amount_to_shares_up(&env, total_to_burn as u128);
+
+            let user_shares = Self::get_shares(&env, &from);
+            assert!(shares_to_burn <= user_shares, "insufficient balance for liquidation");
+
+            Self::burn_for_debt(&env, &bnpl_core, &from, shares_to_burn, amount, fee);
+
+            env.events().publish(
+                (symbol_short!("liq_burn"), from.clone()),
+                LiquidationBurnEvent {
+                    user: from,
+                    amount_burned: total_to_burn,
+                    fee,
+                }
+            );
+        }
+    }
+
+    /// Burn a borrower's own shares to repay their debt (BNPL Core only)
+    /// Unlike `repay_with_burn` this carries no penalty; at most `max_shares` raw shares are burned
+    /// Only the available balance can be burned, so BNPL Core releases the repaid bill's lock first
+    pub fn redeem_to_repay(env: Env, from: Address, amount: i128, fee: i128, max_shares: u128) -> u128 {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+
+        Self::update_index(env.clone());
+
+        let available = Self::available_balance(env.clone(), from.clone());
+        assert!(amount + fee <= available, "insufficient available balance");
+
+        let shares_to_burn = Self::amount_to_shares_up(&env, (amount + fee) as u128);
+        assert!(shares_to_burn <= max_shares, "shares to burn exceed maximum");
+
+        let user_shares = Self::get_shares(&env, &from);
+        assert!(shares_to_burn <= user_shares, "insufficient balance");
+
+        Self::burn_for_debt(&env, &bnpl_core, &from, shares_to_burn, amount, fee);
+
+        env.events().publish(
+            (symbol_short!("rdm_repay"), from.clone()),
+            RedeemRepayEvent {
+                user: from,
+                amount,
+                fee,
+                shares_burned: shares_to_burn,
+            }
+        );
+
+        shares_to_burn
+    }
+
+    /// Burn a holder's shares and pay their value out to BNPL Core as a penalty (BNPL Core only)
+    /// Used to slash LP posted as a merchant bond; only the available balance can be slashed
+    pub fn slash(env: Env, from: Address, amount: i128) -> u128 {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+        assert!(amount > 0, "invalid amount");
+
+        Self::update_index(env.clone());
+
+        let available = Self::available_balance(env.clone(), from.clone());
+        assert!(amount <= available, "insufficient available balance");
+
+        let shares_to_burn = Self::amount_to_shares_up(&env, amount as u128);
+        let user_shares = Self::get_shares(&env, &from);
+        assert!(shares_to_burn <= user_shares, "insufficient balance");
+
+        Self::burn_for_debt(&env, &bnpl_core, &from, shares_to_burn, 0, amount);
+
+        env.events().publish(
+            (symbol_short!("slash"), from.clone()),
+            SlashEvent {
+                user: from,
+                amount,
+                shares_burned: shares_to_burn,
+            }
+        );
+
+        shares_to_burn
+    }
+
+    /// Burn shares against outstanding debt and send the fee part to BNPL Core
+    fn burn_for_debt(env: &Env, bnpl_core: &Address, from: &Address, shares: u128, amount: i128, fee: i128) {
         // Burn shares from user
-        Self::set_shares(&env, &from, user_shares - shares_to_burn);
+        let user_shares = Self::get_shares(env, from);
+        Self::set_shares(env, from, user_shares - shares);
 
         // Update total supply
-        let current_supply = Self::get_supply(&env);
-        Self::set_supply(&env, current_supply - shares_to_burn);
+        let current_supply = Self::get_supply(env);
+        Self::set_supply(env, current_supply - shares);
 
         // Update borrowed amount
         let current_borrowed: u128 = env.storage().instance().get(&symbol_short!("borrowed")).unwrap_or(0);
-        let new_borrowed = if (amount as u128) > current_borrowed {
-            0
-        } else {
-            current_borrowed - (amount as u128)
-        };
+        let new_borrowed = current_borrowed.saturating_sub(amount as u128);
         env.storage().instance().set(&symbol_short!("borrowed"), &new_borrowed);
 
         // Transfer the fee to BNPL Core
         if fee > 0 {
+            let cash = Self::get_cash(env);
+            assert!(fee as u128 <= cash, "insufficient liquidity");
+            Self::set_cash(env, cash - fee as u128);
+
             let underlying_asset: Address = env.storage().instance().get(&symbol_short!("asset")).unwrap();
-            let underlying_client = TokenClient::new(&env, &underlying_asset);
-            underlying_client.transfer(&env.current_contract_address(), &bnpl_core, &fee);
+            let underlying_client = TokenClient::new(env, &underlying_asset);
+            underlying_client.transfer(&env.current_contract_address(), bnpl_core, &fee);
         }
-
-        env.events().publish(
-            (symbol_short!("liq_burn"), from.clone()),
-            LiquidationBurnEvent {
-                user: from,
-                amount_burned: total_to_burn,
-                fee,
-            }
-        );
     }
 
     /// Calculate utilization ratio (borrowed / total_deposits)
@@ -378,15 +877,49 @@ impl LpToken {
         ((total_borrows * 10000) / total_deposits) as u32
     }
 
-    /// Get locked LP tokens for a user based on BNPL Core requirements
-    pub fn get_locked_balance(env: Env, user: Address) -> i128 {
-        let bnpl_core: Address = match env.storage().instance().get(&symbol_short!("bnpl_core")) {
-            Some(addr) => addr,
-            None => return 0,
-        };
+    fn get_locks(env: &Env) -> Map<Address, i128> {
+        env.storage().instance().get(&symbol_short!("locked")).unwrap_or(Map::new(env))
+    }
+
+    /// Lock LP value as collateral for a user's debt (BNPL Core only)
+    /// The user's total lock can never exceed their balance
+    pub fn lock_collateral(env: Env, user: Address, amount: i128) {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+        assert!(amount > 0, "invalid amount");
+
+        let mut locks = Self::get_locks(&env);
+        let locked = locks.get(user.clone()).unwrap_or(0) + amount;
+        assert!(locked <= Self::balance(env.clone(), user.clone()), "insufficient balance to lock");
+        locks.set(user.clone(), locked);
+        env.storage().instance().set(&symbol_short!("locked"), &locks);
+
+        env.events().publish((symbol_short!("lock"), user.clone()), CollateralLockEvent { user, amount, locked });
+    }
+
+    /// Release previously locked collateral (BNPL Core only)
+    pub fn unlock_collateral(env: Env, user: Address, amount: i128) {
+        let bnpl_core: Address = env.storage().instance().get(&symbol_short!("bnpl_core")).unwrap();
+        bnpl_core.require_auth();
+        assert!(amount > 0, "invalid amount");
+
+        let mut locks = Self::get_locks(&env);
+        let current = locks.get(user.clone()).unwrap_or(0);
+        assert!(amount <= current, "unlock exceeds locked balance");
+        let locked = current - amount;
+        if locked == 0 {
+            locks.remove(user.clone());
+        } else {
+            locks.set(user.clone(), locked);
+        }
+        env.storage().instance().set(&symbol_short!("locked"), &locks);
+
+        env.events().publish((symbol_short!("unlock"), user.clone()), CollateralLockEvent { user, amount, locked });
+    }
 
-        let bnpl_client = BnplCoreClient::new(&env, &bnpl_core);
-        bnpl_client.get_user_required_collateral(&user)
+    /// Get LP value locked as collateral by BNPL Core
+    pub fn get_locked_balance(env: Env, user: Address) -> i128 {
+        Self::get_locks(&env).get(user).unwrap_or(0)
     }
 
     /// Get available balance (total balance - locked balance)
@@ -410,16 +943,104 @@ impl LpToken {
         (total, locked, available)
     }
 
-    pub fn mint(env: Env, to: Address, amount: i128) {
+    /// Mint LP tokens to `to` backed by `amount` of underlying paid by the admin (admin only)
+    /// Used to bootstrap liquidity; shares are only issued against assets that arrive in the same call
+    /// Returns the amount of LP tokens (USDC value) credited to `to`
+    pub fn mint(env: Env, to: Address, amount: i128) -> i128 {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+
+        Self::update_index(env.clone());
+
+        let shares_minted = Self::issue_shares(&env, &admin, &to, amount);
+
+        env.events().publish(
+            (symbol_short!("mint"), to.clone()),
+            MintEvent {
+                admin,
+                to,
+                amount,
+                shares_minted,
+            }
+        );
+
+        Self::shares_to_amount(&env, shares_minted) as i128
+    }
+
+    /// Set the maximum total supply in USDC value, enforced on deposit and mint (admin only)
+    /// Pass None to remove the cap
+    pub fn set_supply_cap(env: Env, cap: Option<i128>) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+
+        match cap {
+            Some(cap) => {
+                assert!(cap >= 0, "invalid cap");
+                env.storage().instance().set(&symbol_short!("cap"), &cap);
+            }
+            None => env.storage().instance().remove(&symbol_short!("cap")),
+        }
+    }
+
+    /// Get the supply cap in USDC value (None = uncapped)
+    pub fn supply_cap(env: Env) -> Option<i128> {
+        env.storage().instance().get(&symbol_short!("cap"))
+    }
+
+    /// Set the maximum balance (USDC value) an account may reach through deposits or transfers (admin only)
+    /// Pass None to remove the cap
+    pub fn set_account_cap(env: Env, account: Address, cap: Option<i128>) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+
+        let mut caps = Self::get_account_caps(&env);
+        match cap {
+            Some(cap) => {
+                assert!(cap >= 0, "invalid cap");
+                caps.set(account, cap);
+            }
+            None => {
+                caps.remove(account);
+            }
+        }
+        env.storage().instance().set(&symbol_short!("acct_cap"), &caps);
+    }
+
+    /// Get the balance cap for an account in USDC value (None = uncapped)
+    pub fn account_cap(env: Env, account: Address) -> Option<i128> {
+        Self::get_account_caps(&env).get(account)
+    }
+
+    /// Enable or disable allowlist mode (admin only)
+    /// While enabled only allowlisted accounts can receive LP tokens via deposit, mint or transfer
+    pub fn set_allowlist_enabled(env: Env, enabled: bool) {
         let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
         admin.require_auth();
+        env.storage().instance().set(&symbol_short!("allow_on"), &enabled);
+    }
+
+    /// Whether allowlist mode is enabled
+    pub fn allowlist_enabled(env: Env) -> bool {
+        env.storage().instance().get(&symbol_short!("allow_on")).unwrap_or(false)
+    }
 
-        let shares_to_mint = Self::amount_to_shares(&env, amount as u128);
-        let current_shares = Self::get_shares(&env, &to);
-        Self::set_shares(&env, &to, current_shares + shares_to_mint);
+    /// Add or remove an account from the allowlist (admin only)
+    pub fn set_allowlisted(env: Env, account: Address, allowed: bool) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+
+        let mut allowlist = Self::get_allowlist(&env);
+        if allowed {
+            allowlist.set(account, true);
+        } else {
+            allowlist.remove(account);
+        }
+        env.storage().instance().set(&symbol_short!("allowlist"), &allowlist);
+    }
 
-        let new_supply = Self::get_supply(&env) + shares_to_mint;
-        Self::set_supply(&env, new_supply);
+    /// Whether an account is on the allowlist
+    pub fn is_allowlisted(env: Env, account: Address) -> bool {
+        Self::get_allowlist(&env).get(account).unwrap_or(false)
     }
 
     pub fn metadata(env: Env) -> TokenMetadata {
@@ -480,40 +1101,19 @@ impl TokenInterface for LpToken {
 
     fn burn(env: Env, from: Address, amount: i128) {
         from.require_auth();
-
-        let available = Self::available_balance(env.clone(), from.clone());
-        assert!(amount <= available, "insufficient available balance");
-
-        let shares_to_burn = Self::amount_to_shares(&env, amount as u128);
-        let user_shares = Self::get_shares(&env, &from);
-        assert!(shares_to_burn <= user_shares, "insufficient balance");
-
-        Self::set_shares(&env, &from, user_shares - shares_to_burn);
-
-        let current_supply = Self::get_supply(&env);
-        Self::set_supply(&env, current_supply - shares_to_burn);
+        Self::burn_forfeit(&env, &from, amount);
     }
 
     fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
         spender.require_auth();
 
-        let available = Self::available_balance(env.clone(), from.clone());
-        assert!(amount <= available, "insufficient available balance");
-
         let mut allowances: Map<(Address, Address), u128> = env.storage().instance().get(&symbol_short!("allowance")).unwrap_or(Map::new(&env));
         let current = allowances.get((from.clone(), spender.clone())).unwrap_or(0) as i128;
         assert!(current >= amount, "allowance exceeded");
         allowances.set((from.clone(), spender.clone()), (current - amount) as u128);
         env.storage().instance().set(&symbol_short!("allowance"), &allowances);
 
-        let shares_to_burn = Self::amount_to_shares(&env, amount as u128);
-        let user_shares = Self::get_shares(&env, &from);
-        assert!(shares_to_burn <= user_shares, "insufficient balance");
-
-        Self::set_shares(&env, &from, user_shares - shares_to_burn);
-
-        let current_supply = Self::get_supply(&env);
-        Self::set_supply(&env, current_supply - shares_to_burn);
+        Self::burn_forfeit(&env, &from, amount);
     }
 
     fn decimals(_env: Env) -> u32 {
@@ -1,5 +1,5 @@
 use crate::*;
-use soroban_sdk::{testutils::Address as _, String, Env, contract, contractimpl, contractclient};
+use soroban_sdk::{testutils::{Address as _, Ledger}, String, Env, contract, contractimpl, contractclient};
 use soroban_sdk::token::{StellarAssetClient, TokenClient};
 use soroban_token_sdk::metadata::TokenMetadata;
 
@@ -9,16 +9,6 @@ pub struct MockBnplCore;
 
 #[contractimpl]
 impl MockBnplCore {
-    pub fn get_user_required_collateral(_env: Env, user: Address) -> i128 {
-        // Return test values based on user address
-        // This is for demonstration - in real implementation this would calculate based on user's debt
-        if user.to_string() == String::from_str(&_env, "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAFCT4") {
-            500 // This user has 500 locked
-        } else {
-            0 // Other users have no locked balance
-        }
-    }
-    
     pub fn get_user_total_debt(_env: Env, _user: Address) -> i128 {
         0 // Simple mock
     }
@@ -28,17 +18,37 @@ impl MockBnplCore {
 #[contractclient(name = "LpTokenTestClient")]
 trait _LpTokenTestTrait {
     fn initialize(env: Env, admin: Address, underlying_asset: Address, metadata: TokenMetadata);
-    fn mint(env: Env, to: Address, amount: i128);
+    fn mint(env: Env, to: Address, amount: i128) -> i128;
+    fn set_supply_cap(env: Env, cap: Option<i128>);
+    fn supply_cap(env: Env) -> Option<i128>;
+    fn set_account_cap(env: Env, account: Address, cap: Option<i128>);
+    fn account_cap(env: Env, account: Address) -> Option<i128>;
+    fn set_allowlist_enabled(env: Env, enabled: bool);
+    fn allowlist_enabled(env: Env) -> bool;
+    fn set_allowlisted(env: Env, account: Address, allowed: bool);
+    fn is_allowlisted(env: Env, account: Address) -> bool;
+    fn set_vesting_period(env: Env, period: u64);
+    fn vesting_period(env: Env) -> u64;
+    fn unvested_rewards(env: Env) -> i128;
+    fn projected_index(env: Env, timestamp: u64) -> u128;
+    fn set_snapshot_interval(env: Env, interval: u64);
+    fn snapshot_interval(env: Env) -> u64;
+    fn get_snapshots(env: Env) -> soroban_sdk::Vec<IndexSnapshot>;
+    fn apy_7d(env: Env) -> u32;
+    fn apy_30d(env: Env) -> u32;
     fn balance(env: Env, user: Address) -> i128;
     fn transfer(env: Env, from: Address, to: Address, amount: i128);
     fn deposit(env: Env, from: Address, amount: i128) -> i128;
     fn withdraw(env: Env, from: Address, lp_amount: i128) -> i128;
     fn get_locked_balance(env: Env, user: Address) -> i128;
+    fn lock_collateral(env: Env, user: Address, amount: i128);
+    fn unlock_collateral(env: Env, user: Address, amount: i128);
     fn available_balance(env: Env, user: Address) -> i128;
     fn get_balance_info(env: Env, user: Address) -> (i128, i128, i128);
     fn set_bnpl_core(env: Env, bnpl_core: Address);
     fn get_bnpl_core(env: Env) -> Option<Address>;
     fn update_index(env: Env);
+    fn distribute_yield(env: Env, from: Address, amount: i128);
     fn exchange_rate(env: Env) -> u128;
     fn borrow(env: Env, to: Address, amount: i128);
     fn repay(env: Env, from: Address, amount: i128);
@@ -49,6 +59,10 @@ trait _LpTokenTestTrait {
     fn underlying_asset(env: Env) -> Address;
     fn total_underlying(env: Env) -> i128;
     fn repay_with_burn(env: Env, from: Address, amount: i128, fee: i128);
+    fn repay_with_burn_batch(env: Env, burns: soroban_sdk::Vec<(Address, i128, i128)>);
+    fn redeem_to_repay(env: Env, from: Address, amount: i128, fee: i128, max_shares: u128) -> u128;
+    fn slash(env: Env, from: Address, amount: i128) -> u128;
+    fn raw_shares(env: Env, user: Address) -> u128;
 }
 
 #[test]
@@ -101,15 +115,16 @@ fn test_deposit_and_withdraw() {
     // Mint underlying tokens to user
     underlying_client.mint(&user, &1_000_000);
     
-    // Test deposit
+    // Test deposit (first deposit locks MINIMUM_LIQUIDITY)
     let deposited = lp_client.deposit(&user, &100_000);
-    assert_eq!(deposited, 100_000);
-    assert_eq!(lp_client.balance(&user), 100_000);
+    assert_eq!(deposited, 100_000 - MINIMUM_LIQUIDITY as i128);
+    assert_eq!(lp_client.balance(&user), 100_000 - MINIMUM_LIQUIDITY as i128);
+    assert_eq!(lp_client.raw_shares(&lp_contract_id), MINIMUM_LIQUIDITY);
     
     // Test withdraw
     let withdrawn = lp_client.withdraw(&user, &50_000);
     assert_eq!(withdrawn, 50_000);
-    assert_eq!(lp_client.balance(&user), 50_000);
+    assert_eq!(lp_client.balance(&user), 50_000 - MINIMUM_LIQUIDITY as i128);
 }
 
 #[test]
@@ -136,6 +151,7 @@ fn test_rebasing_mechanism() {
     };
     
     lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&Address::generate(&env));
     
     // Mint underlying tokens to users
     underlying_client.mint(&user1, &1_000_000);
@@ -146,19 +162,17 @@ fn test_rebasing_mechanism() {
     lp_client.deposit(&user1, &100_000);
     lp_client.deposit(&user2, &100_000);
     
-    // Initial balances
-    assert_eq!(lp_client.balance(&user1), 100_000);
+    // Initial balances (user1 made the first deposit and locked MINIMUM_LIQUIDITY)
+    assert_eq!(lp_client.balance(&user1), 99_000);
     assert_eq!(lp_client.balance(&user2), 100_000);
     
-    // Send yield to LP contract  
+    // Distribute yield through BNPL Core
     underlying_client.mint(&admin, &20_000);
-    TokenClient::new(&env, &underlying.address()).transfer(&admin, &lp_contract_id, &20_000);
-    
-    // Update index to distribute yield
-    lp_client.update_index();
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &20_000, &0);
+    lp_client.distribute_yield(&admin, &20_000);
     
     // Check balances increased proportionally
-    assert_eq!(lp_client.balance(&user1), 110_000); // 100k + 10%
+    assert_eq!(lp_client.balance(&user1), 108_900); // 99k + 10%
     assert_eq!(lp_client.balance(&user2), 110_000); // 100k + 10%
 }
 
@@ -185,7 +199,8 @@ fn test_simple_transfer() {
     lp_client.initialize(&admin, &underlying.address(), &metadata);
     
     // Mint LP tokens to user
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
     assert_eq!(lp_client.balance(&user), 1000);
     
     // Transfer
@@ -220,7 +235,8 @@ fn test_locked_balance_prevents_withdraw() {
     // First, deposit underlying assets to get LP tokens
     let underlying_client = StellarAssetClient::new(&env, &underlying.address());
     underlying_client.mint(&user, &10_000);
-    lp_client.deposit(&user, &1000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    lp_client.lock_collateral(&user, &500); // BNPL Core locks collateral for the debt
     
     // Check locked balance
     assert_eq!(lp_client.get_locked_balance(&user), 500);
@@ -258,7 +274,8 @@ fn test_withdraw_exceeding_available_panics() {
     // First, deposit underlying assets to get LP tokens
     let underlying_client = StellarAssetClient::new(&env, &underlying.address());
     underlying_client.mint(&user, &10_000);
-    lp_client.deposit(&user, &1000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    lp_client.lock_collateral(&user, &500); // BNPL Core locks collateral for the debt
     
     // Try to withdraw more than available (should panic)
     lp_client.withdraw(&user, &600); // Has 1000 but 500 locked
@@ -294,7 +311,9 @@ fn test_locked_balance_prevents_transfer() {
     lp_client.set_bnpl_core(&bnpl_core_id);
     
     // Mint LP tokens to user
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
+    lp_client.lock_collateral(&user, &500); // BNPL Core locks collateral for the debt
     
     // Check initial balance
     assert_eq!(lp_client.balance(&user), 1000);
@@ -340,7 +359,9 @@ fn test_transfer_exceeding_available_panics() {
     lp_client.set_bnpl_core(&bnpl_core_id);
     
     // Mint LP tokens to user
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
+    lp_client.lock_collateral(&user, &500); // BNPL Core locks collateral for the debt
     
     // Try to transfer more than available (should panic)
     lp_client.transfer(&user, &recipient, &600); // Has 1000 but 500 locked
@@ -373,7 +394,7 @@ fn test_borrow_repay_tracking() {
     
     // Add liquidity to the LP token
     underlying_client.mint(&admin, &1_000_000);
-    underlying_token_client.transfer(&admin, &lp_contract_id, &500_000);
+    lp_client.deposit(&admin, &500_000);
     
     // Initial state
     assert_eq!(lp_client.total_borrowed(), 0);
@@ -445,7 +466,8 @@ fn test_locked_balance_without_bnpl() {
     lp_client.initialize(&admin, &underlying.address(), &metadata);
     
     // Mint some tokens
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
     
     // Without BNPL Core set, locked balance should be 0
     assert_eq!(lp_client.get_locked_balance(&user), 0);
@@ -480,7 +502,8 @@ fn test_burn() {
     lp_client.initialize(&admin, &underlying.address(), &metadata);
     
     // Mint tokens to user
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
     assert_eq!(lp_client.balance(&user), 1000);
     
     // Burn tokens
@@ -546,7 +569,8 @@ fn test_transfer_from() {
     lp_client.initialize(&admin, &underlying.address(), &metadata);
     
     // Mint tokens to owner
-    lp_client.mint(&owner, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&owner, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
     
     // Approve spender
     token_client.approve(&owner, &spender, &600, &100000);
@@ -585,7 +609,8 @@ fn test_burn_from() {
     lp_client.initialize(&admin, &underlying.address(), &metadata);
     
     // Mint tokens to owner
-    lp_client.mint(&owner, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&owner, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
     
     // Approve spender
     token_client.approve(&owner, &spender, &600, &100000);
@@ -652,7 +677,9 @@ fn test_burn_with_locked_balance() {
     lp_client.set_bnpl_core(&bnpl_core_id);
     
     // Mint tokens to user
-    lp_client.mint(&user, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
+    lp_client.lock_collateral(&user, &500); // BNPL Core locks collateral for the debt
     
     // Try to burn more than available (should panic)
     token_client.burn(&user, &600); // Has 1000 but 500 locked
@@ -685,7 +712,9 @@ fn test_burn_from_with_locked_balance() {
     lp_client.set_bnpl_core(&bnpl_core_id);
     
     // Mint tokens to owner
-    lp_client.mint(&owner, &1000);
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&owner, &(MINIMUM_LIQUIDITY as i128 + 1000)); // first issuance locks MINIMUM_LIQUIDITY
+    lp_client.lock_collateral(&owner, &500); // BNPL Core locks collateral for the debt
     
     // Approve spender
     token_client.approve(&owner, &spender, &1000, &100000);
@@ -742,20 +771,21 @@ fn test_total_supply() {
     // Initially zero supply
     assert_eq!(lp_client.total_supply(), 0);
     
-    // Mint to users
-    lp_client.mint(&user1, &1000);
-    assert_eq!(lp_client.total_supply(), 1000);
+    // Mint to users (the first issuance also locks MINIMUM_LIQUIDITY)
+    StellarAssetClient::new(&env, &underlying.address()).mint(&admin, &10_000);
+    lp_client.mint(&user1, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    assert_eq!(lp_client.total_supply(), MINIMUM_LIQUIDITY as i128 + 1000);
     
     lp_client.mint(&user2, &500);
-    assert_eq!(lp_client.total_supply(), 1500);
+    assert_eq!(lp_client.total_supply(), MINIMUM_LIQUIDITY as i128 + 1500);
     
     // Burn from user1
     token_client.burn(&user1, &200);
-    assert_eq!(lp_client.total_supply(), 1300);
+    assert_eq!(lp_client.total_supply(), MINIMUM_LIQUIDITY as i128 + 1300);
     
     // Transfer doesn't affect total supply
     lp_client.transfer(&user1, &user2, &300);
-    assert_eq!(lp_client.total_supply(), 1300);
+    assert_eq!(lp_client.total_supply(), MINIMUM_LIQUIDITY as i128 + 1300);
 }
 
 #[test]
@@ -808,6 +838,7 @@ fn test_total_supply_with_rebasing() {
     };
     
     lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&Address::generate(&env));
     
     // Mint underlying tokens to users
     underlying_client.mint(&user1, &1_000_000);
@@ -818,21 +849,19 @@ fn test_total_supply_with_rebasing() {
     lp_client.deposit(&user1, &100_000);
     lp_client.deposit(&user2, &100_000);
     
-    // Initial total supply
+    // Initial total supply (includes the locked minimum liquidity)
     assert_eq!(lp_client.total_supply(), 200_000);
     
-    // Send yield to LP contract
+    // Distribute yield through BNPL Core
     underlying_client.mint(&admin, &20_000);
-    TokenClient::new(&env, &underlying.address()).transfer(&admin, &lp_contract_id, &20_000);
-    
-    // Update index to distribute yield
-    lp_client.update_index();
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &20_000, &0);
+    lp_client.distribute_yield(&admin, &20_000);
     
     // Total supply should increase after rebasing
     assert_eq!(lp_client.total_supply(), 220_000); // 200k + 10%
     
     // Individual balances should also reflect the increase
-    assert_eq!(lp_client.balance(&user1), 110_000);
+    assert_eq!(lp_client.balance(&user1), 108_900);
     assert_eq!(lp_client.balance(&user2), 110_000);
 }
 
@@ -874,15 +903,13 @@ fn test_update_index_with_borrowed_amount() {
     assert_eq!(lp_client.total_underlying(), 600_000);
     assert_eq!(lp_client.total_borrowed(), 400_000);
     
-    // Send 100,000 as yield (10% on total 1,000,000)
+    // Distribute 100,000 as yield (10% on total 1,000,000)
     underlying_client.mint(&admin, &100_000);
-    TokenClient::new(&env, &underlying.address()).transfer(&admin, &lp_contract_id, &100_000);
-    
-    // Update index to distribute yield
-    lp_client.update_index();
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &100_000, &0);
+    lp_client.distribute_yield(&admin, &100_000);
     
-    // User balance should increase by 10% (from 1,000,000 to 1,100,000)
-    assert_eq!(lp_client.balance(&user), 1_100_000);
+    // User balance should increase by 10% (from 999,000 to 1,098,900)
+    assert_eq!(lp_client.balance(&user), 1_098_900);
     
     // Total supply should also increase
     assert_eq!(lp_client.total_supply(), 1_100_000);
@@ -924,14 +951,17 @@ fn test_repay_with_burn() {
     lp_client.borrow(&borrower, &400_000);
     
     // Initial state
-    assert_eq!(lp_client.balance(&user), 1_000_000);
+    assert_eq!(lp_client.balance(&user), 999_000);
     assert_eq!(lp_client.total_borrowed(), 400_000);
     
     // Liquidate: repay 200,000 with 10,000 fee
     lp_client.repay_with_burn(&user, &200_000, &10_000);
     
     // Check user balance decreased by 210,000 (200k + 10k fee)
-    assert_eq!(lp_client.balance(&user), 790_000);
+    assert_eq!(lp_client.balance(&user), 789_000);
+    
+    // Fee left the pool's accounted cash
+    assert_eq!(lp_client.total_underlying(), 590_000);
     
     // Check borrowed amount decreased by 200,000 (not including fee)
     assert_eq!(lp_client.total_borrowed(), 200_000);
@@ -941,4 +971,857 @@ fn test_repay_with_burn() {
     
     // Check total supply decreased by 210,000
     assert_eq!(lp_client.total_supply(), 790_000);
-}
\ No newline at end of file
+}
+#[test]
+fn test_donation_does_not_inflate_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let victim = Address::generate(&env);
+    
+    // Deploy underlying asset
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    // Deploy LP token
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    
+    underlying_client.mint(&attacker, &10_000_000);
+    underlying_client.mint(&victim, &1_000_000);
+    
+    // Attacker makes the first deposit and keeps a single share
+    lp_client.deposit(&attacker, &(MINIMUM_LIQUIDITY as i128 + 1));
+    assert_eq!(lp_client.raw_shares(&attacker), 1);
+    
+    // Attacker donates directly to the pool and pokes the index
+    underlying_token.transfer(&attacker, &lp_contract_id, &5_000_000);
+    lp_client.update_index();
+    
+    // Donation is not accounted, so the index doesn't move
+    assert_eq!(lp_client.exchange_rate(), DECIMALS);
+    assert_eq!(lp_client.total_underlying(), MINIMUM_LIQUIDITY as i128 + 1);
+    
+    // Victim still receives the full value of their deposit
+    let credited = lp_client.deposit(&victim, &100_000);
+    assert_eq!(credited, 100_000);
+    assert_eq!(lp_client.balance(&victim), 100_000);
+    
+    // Attacker can't claim the donation back through their share
+    assert_eq!(lp_client.balance(&attacker), 1);
+    
+    // Burning shares forfeits their value instead of raising the index
+    TokenClient::new(&env, &lp_contract_id).burn(&victim, &50_000);
+    lp_client.update_index();
+    assert_eq!(lp_client.exchange_rate(), DECIMALS);
+    assert_eq!(lp_client.balance(&victim), 50_000);
+    assert_eq!(lp_client.total_underlying(), MINIMUM_LIQUIDITY as i128 + 1 + 50_000);
+}
+
+#[test]
+#[should_panic(expected = "deposit too small")]
+fn test_first_deposit_below_minimum_liquidity_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    
+    underlying_client.mint(&user, &10_000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128));
+}
+
+#[test]
+fn test_withdraw_rounds_shares_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&env.register(MockBnplCore, ()));
+    
+    underlying_client.mint(&user, &1_000_000);
+    lp_client.deposit(&user, &101_000);
+    
+    // Push the index to 1.1
+    underlying_client.mint(&admin, &10_100);
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &10_100, &0);
+    lp_client.distribute_yield(&admin, &10_100);
+    assert_eq!(lp_client.exchange_rate(), 1_100_000_000);
+    
+    // Withdrawing 1 unit is worth less than a share, but still burns one
+    let shares_before = lp_client.raw_shares(&user);
+    lp_client.withdraw(&user, &1);
+    assert_eq!(lp_client.raw_shares(&user), shares_before - 1);
+}
+
+#[test]
+fn test_mint_is_backed_by_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    
+    underlying_client.mint(&admin, &100_000);
+    let credited = lp_client.mint(&user, &50_000);
+    
+    // Shares are issued only against the underlying paid in by the admin
+    assert_eq!(credited, 50_000 - MINIMUM_LIQUIDITY as i128);
+    assert_eq!(lp_client.balance(&user), 50_000 - MINIMUM_LIQUIDITY as i128);
+    assert_eq!(underlying_token.balance(&admin), 50_000);
+    assert_eq!(lp_client.total_underlying(), 50_000);
+    assert_eq!(lp_client.exchange_rate(), DECIMALS);
+}
+
+#[test]
+#[should_panic(expected = "supply cap exceeded")]
+fn test_mint_respects_supply_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_supply_cap(&Some(60_000));
+    assert_eq!(lp_client.supply_cap(), Some(60_000));
+    
+    underlying_client.mint(&admin, &100_000);
+    lp_client.mint(&user, &50_000);
+    
+    // Pushes total supply to 70,000 > 60,000
+    lp_client.mint(&user, &20_000);
+}
+
+#[test]
+#[should_panic(expected = "Already initialized")]
+fn test_double_initialization() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+}
+
+#[test]
+#[should_panic(expected = "account not allowlisted")]
+fn test_allowlist_blocks_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_allowlist_enabled(&true);
+    assert!(lp_client.allowlist_enabled());
+    
+    underlying_client.mint(&user, &100_000);
+    lp_client.deposit(&user, &100_000);
+}
+
+#[test]
+fn test_allowlist_restricts_transfers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let allowed = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_allowlist_enabled(&true);
+    lp_client.set_allowlisted(&user, &true);
+    lp_client.set_allowlisted(&allowed, &true);
+    assert!(lp_client.is_allowlisted(&user));
+    
+    underlying_client.mint(&user, &100_000);
+    lp_client.deposit(&user, &100_000);
+    
+    // Transfers between allowlisted accounts go through
+    lp_client.transfer(&user, &allowed, &10_000);
+    assert_eq!(lp_client.balance(&allowed), 10_000);
+    
+    // Removed accounts can no longer receive
+    lp_client.set_allowlisted(&allowed, &false);
+    assert!(!lp_client.is_allowlisted(&allowed));
+    let result = lp_client.try_transfer(&user, &allowed, &10_000);
+    assert!(result.is_err());
+    
+    // Disabling the mode lifts the restriction
+    lp_client.set_allowlist_enabled(&false);
+    lp_client.transfer(&user, &allowed, &10_000);
+    assert_eq!(lp_client.balance(&allowed), 20_000);
+}
+
+#[test]
+fn test_account_cap_limits_deposit_and_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let capped = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_account_cap(&capped, &Some(50_000));
+    assert_eq!(lp_client.account_cap(&capped), Some(50_000));
+    
+    underlying_client.mint(&user, &100_000);
+    underlying_client.mint(&capped, &100_000);
+    lp_client.deposit(&user, &100_000);
+    
+    // Deposit up to the cap
+    lp_client.deposit(&capped, &40_000);
+    assert!(lp_client.try_deposit(&capped, &20_000).is_err());
+    
+    // Transfers can't push the account over its cap either
+    assert!(lp_client.try_transfer(&user, &capped, &20_000).is_err());
+    lp_client.transfer(&user, &capped, &10_000);
+    assert_eq!(lp_client.balance(&capped), 50_000);
+    
+    // Removing the cap lifts the limit
+    lp_client.set_account_cap(&capped, &None);
+    assert_eq!(lp_client.account_cap(&capped), None);
+    lp_client.deposit(&capped, &20_000);
+    assert_eq!(lp_client.balance(&capped), 70_000);
+}
+
+#[test]
+fn test_yield_vests_linearly() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&env.register(MockBnplCore, ()));
+    lp_client.set_vesting_period(&86_400);
+    assert_eq!(lp_client.vesting_period(), 86_400);
+    
+    underlying_client.mint(&user, &1_000_000);
+    lp_client.deposit(&user, &100_000);
+    
+    // Distribute 10,000 of yield, streamed over one day
+    let start = env.ledger().timestamp();
+    underlying_client.mint(&admin, &10_000);
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &10_000, &0);
+    lp_client.distribute_yield(&admin, &10_000);
+    
+    // Nothing is released yet
+    assert_eq!(lp_client.exchange_rate(), DECIMALS);
+    assert_eq!(lp_client.unvested_rewards(), 10_000);
+    assert_eq!(lp_client.projected_index(&(start + 86_400)), 1_100_000_000);
+    
+    // Half way through the window half the yield is in the index
+    env.ledger().with_mut(|li| {
+        li.timestamp = start + 43_200;
+    });
+    assert_eq!(lp_client.unvested_rewards(), 5_000);
+    lp_client.update_index();
+    assert_eq!(lp_client.exchange_rate(), 1_050_000_000);
+    assert_eq!(lp_client.projected_index(&(start + 86_400)), 1_100_000_000);
+    
+    // Fully vested after the window
+    env.ledger().with_mut(|li| {
+        li.timestamp = start + 86_400;
+    });
+    lp_client.update_index();
+    assert_eq!(lp_client.exchange_rate(), 1_100_000_000);
+    assert_eq!(lp_client.unvested_rewards(), 0);
+}
+
+#[test]
+fn test_new_yield_restarts_vesting_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&env.register(MockBnplCore, ()));
+    lp_client.set_vesting_period(&86_400);
+    
+    underlying_client.mint(&user, &100_000);
+    lp_client.deposit(&user, &100_000);
+    
+    let start = env.ledger().timestamp();
+    underlying_client.mint(&admin, &20_000);
+    underlying_token.approve(&admin, &lp_contract_id, &20_000, &0);
+    lp_client.distribute_yield(&admin, &10_000);
+    
+    // Half way through, a second payout arrives
+    env.ledger().with_mut(|li| {
+        li.timestamp = start + 43_200;
+    });
+    lp_client.distribute_yield(&admin, &10_000);
+    assert_eq!(lp_client.exchange_rate(), 1_050_000_000);
+    assert_eq!(lp_client.unvested_rewards(), 15_000);
+    
+    // The 5,000 left from the first payout is spread over the new window with the rest,
+    // so at the original end time half of the merged amount is still pending
+    env.ledger().with_mut(|li| {
+        li.timestamp = start + 86_400;
+    });
+    assert_eq!(lp_client.unvested_rewards(), 7_500);
+    
+    // Everything is released by the end of the restarted window
+    env.ledger().with_mut(|li| {
+        li.timestamp = start + 43_200 + 86_400;
+    });
+    lp_client.update_index();
+    assert_eq!(lp_client.unvested_rewards(), 0);
+    assert_eq!(lp_client.exchange_rate(), 1_200_000_000);
+}
+
+#[test]
+fn test_deposit_before_yield_does_not_capture_it() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let sniper = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&env.register(MockBnplCore, ()));
+    lp_client.set_vesting_period(&(7 * 86_400));
+    
+    underlying_client.mint(&user, &100_000);
+    underlying_client.mint(&sniper, &1_000_000);
+    lp_client.deposit(&user, &100_000);
+    
+    // Sniper deposits right before a large payout and withdraws right after
+    lp_client.deposit(&sniper, &1_000_000);
+    underlying_client.mint(&admin, &50_000);
+    underlying_token.approve(&admin, &lp_contract_id, &50_000, &0);
+    lp_client.distribute_yield(&admin, &50_000);
+    
+    let sniper_balance = lp_client.balance(&sniper);
+    lp_client.withdraw(&sniper, &sniper_balance);
+    
+    // Sniper only gets their principal back
+    assert_eq!(underlying_token.balance(&sniper), 1_000_000);
+    assert_eq!(lp_client.unvested_rewards(), 50_000);
+}
+
+#[test]
+fn test_snapshots_ring_buffer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    assert_eq!(lp_client.snapshot_interval(), 86_400);
+    lp_client.set_snapshot_interval(&10);
+    
+    // 70 updates, one per interval
+    for i in 0..70u64 {
+        env.ledger().with_mut(|li| {
+            li.timestamp = i * 10;
+        });
+        lp_client.update_index();
+    }
+    
+    // Only the latest MAX_SNAPSHOTS are kept, oldest first
+    let snapshots = lp_client.get_snapshots();
+    assert_eq!(snapshots.len(), MAX_SNAPSHOTS);
+    assert_eq!(snapshots.get(0).unwrap().timestamp, 60);
+    assert_eq!(snapshots.get(MAX_SNAPSHOTS - 1).unwrap().timestamp, 690);
+    
+    // A second update within the same interval doesn't write
+    env.ledger().with_mut(|li| {
+        li.timestamp = 695;
+    });
+    lp_client.update_index();
+    let snapshots = lp_client.get_snapshots();
+    assert_eq!(snapshots.get(MAX_SNAPSHOTS - 1).unwrap().timestamp, 690);
+}
+
+#[test]
+fn test_trailing_apy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&Address::generate(&env));
+    
+    // Deposit records the first snapshot at index 1.0
+    underlying_client.mint(&user, &100_000);
+    lp_client.deposit(&user, &100_000);
+    assert_eq!(lp_client.apy_7d(), 0);
+    
+    // One week later the index grows by 1%
+    env.ledger().with_mut(|li| {
+        li.timestamp = 7 * 86_400;
+    });
+    underlying_client.mint(&admin, &1_000);
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &1_000, &0);
+    lp_client.distribute_yield(&admin, &1_000);
+    assert_eq!(lp_client.exchange_rate(), 1_010_000_000);
+    assert_eq!(lp_client.get_snapshots().len(), 2);
+    
+    // 1% over 7 days = 52.14% annualized
+    assert_eq!(lp_client.apy_7d(), 5214);
+    
+    // History is shorter than 30 days, so the oldest snapshot is used
+    assert_eq!(lp_client.apy_30d(), 5214);
+}
+
+#[test]
+fn test_trailing_apy_saturates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&Address::generate(&env));
+    
+    underlying_client.mint(&user, &100_000);
+    lp_client.deposit(&user, &100_000);
+    
+    // Doubling the index in one second annualizes far beyond u32
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1;
+    });
+    underlying_client.mint(&admin, &100_000);
+    TokenClient::new(&env, &underlying.address()).approve(&admin, &lp_contract_id, &100_000, &0);
+    lp_client.distribute_yield(&admin, &100_000);
+    assert_eq!(lp_client.apy_7d(), u32::MAX);
+}
+
+#[test]
+fn test_unlock_collateral_releases_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let bnpl_core_id = env.register(MockBnplCore, ());
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core_id);
+    
+    StellarAssetClient::new(&env, &underlying.address()).mint(&user, &10_000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    
+    // Locks accumulate per user
+    lp_client.lock_collateral(&user, &300);
+    lp_client.lock_collateral(&user, &400);
+    assert_eq!(lp_client.get_locked_balance(&user), 700);
+    assert_eq!(lp_client.available_balance(&user), 300);
+    
+    // Unlocking releases the balance
+    lp_client.unlock_collateral(&user, &300);
+    assert_eq!(lp_client.get_locked_balance(&user), 400);
+    lp_client.unlock_collateral(&user, &400);
+    assert_eq!(lp_client.get_locked_balance(&user), 0);
+    assert_eq!(lp_client.available_balance(&user), 1000);
+}
+
+#[test]
+#[should_panic(expected = "unlock exceeds locked balance")]
+fn test_unlock_more_than_locked_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let bnpl_core_id = env.register(MockBnplCore, ());
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core_id);
+    
+    StellarAssetClient::new(&env, &underlying.address()).mint(&user, &10_000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    
+    lp_client.lock_collateral(&user, &300);
+    lp_client.unlock_collateral(&user, &301);
+}
+
+#[test]
+#[should_panic(expected = "insufficient balance to lock")]
+fn test_lock_more_than_balance_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    
+    let bnpl_core_id = env.register(MockBnplCore, ());
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core_id);
+    
+    StellarAssetClient::new(&env, &underlying.address()).mint(&user, &10_000);
+    lp_client.deposit(&user, &(MINIMUM_LIQUIDITY as i128 + 1000));
+    
+    // Locks add up, so the second one takes the total past the balance
+    lp_client.lock_collateral(&user, &600);
+    lp_client.lock_collateral(&user, &600);
+}
+
+#[test]
+fn test_redeem_to_repay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let bnpl_core = Address::generate(&env);
+    
+    // Deploy underlying asset
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    // Deploy LP token
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core);
+    
+    // User deposits 1,000,000 and borrows 200,000 against it
+    underlying_client.mint(&user, &1_000_000);
+    lp_client.deposit(&user, &1_000_000);
+    lp_client.borrow(&user, &200_000);
+    
+    // Burning more shares than allowed is rejected
+    let result = lp_client.try_redeem_to_repay(&user, &200_000, &5_000, &204_999);
+    assert!(result.is_err());
+    
+    // LP locked for other debt can't be burned
+    lp_client.lock_collateral(&user, &800_000);
+    let result = lp_client.try_redeem_to_repay(&user, &200_000, &5_000, &205_000);
+    assert!(result.is_err());
+    
+    // Releasing the repaid bill's own lock makes enough available
+    lp_client.unlock_collateral(&user, &100_000);
+    
+    // Self-repayment burns exactly debt plus late fee, no penalty
+    let shares_burned = lp_client.redeem_to_repay(&user, &200_000, &5_000, &205_000);
+    assert_eq!(shares_burned, 205_000);
+    assert_eq!(lp_client.balance(&user), 999_000 - 205_000);
+    assert_eq!(lp_client.available_balance(&user), 999_000 - 205_000 - 700_000);
+    assert_eq!(lp_client.total_borrowed(), 0);
+    assert_eq!(underlying_token.balance(&bnpl_core), 5_000);
+}
+
+#[test]
+fn test_slash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let bnpl_core = Address::generate(&env);
+    
+    // Deploy underlying asset
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    // Deploy LP token
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core);
+    
+    underlying_client.mint(&merchant, &1_000_000);
+    lp_client.deposit(&merchant, &1_000_000);
+    lp_client.lock_collateral(&merchant, &900_000);
+    
+    // Locked LP can't be slashed
+    let result = lp_client.try_slash(&merchant, &100_000);
+    assert!(result.is_err());
+    
+    // Slashing burns the shares and pays their value to BNPL Core without touching borrows
+    let shares_burned = lp_client.slash(&merchant, &99_000);
+    assert_eq!(shares_burned, 99_000);
+    assert_eq!(lp_client.balance(&merchant), 900_000);
+    assert_eq!(lp_client.total_borrowed(), 0);
+    assert_eq!(underlying_token.balance(&bnpl_core), 99_000);
+}
+
+#[test]
+fn test_repay_with_burn_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let admin = Address::generate(&env);
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let bnpl_core = Address::generate(&env);
+    let borrower = Address::generate(&env);
+    
+    // Deploy underlying asset
+    let underlying = env.register_stellar_asset_contract_v2(admin.clone());
+    let underlying_client = StellarAssetClient::new(&env, &underlying.address());
+    let underlying_token = TokenClient::new(&env, &underlying.address());
+    
+    // Deploy LP token
+    let lp_contract_id = env.register(LpToken, ());
+    let lp_client = LpTokenTestClient::new(&env, &lp_contract_id);
+    
+    let metadata = TokenMetadata {
+        name: String::from_str(&env, "LP Token"),
+        symbol: String::from_str(&env, "LP"),
+        decimal: 9,
+    };
+    
+    lp_client.initialize(&admin, &underlying.address(), &metadata);
+    lp_client.set_bnpl_core(&bnpl_core);
+    
+    // Two users deposit, BNPL Core borrows 400,000
+    underlying_client.mint(&user_1, &1_000_000);
+    underlying_client.mint(&user_2, &500_000);
+    lp_client.deposit(&user_1, &1_000_000);
+    lp_client.deposit(&user_2, &500_000);
+    lp_client.borrow(&borrower, &400_000);
+    
+    // Liquidate both users in one call
+    lp_client.repay_with_burn_batch(&soroban_sdk::vec![&env, (user_1.clone(), 200_000, 10_000), (user_2.clone(), 100_000, 5_000)]);
+    
+    assert_eq!(lp_client.balance(&user_1), 999_000 - 210_000);
+    assert_eq!(lp_client.balance(&user_2), 500_000 - 105_000);
+    assert_eq!(lp_client.total_borrowed(), 100_000);
+    assert_eq!(underlying_token.balance(&bnpl_core), 15_000);
+}
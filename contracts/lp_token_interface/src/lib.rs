@@ -1,6 +1,6 @@
 #![no_std]
 use soroban_sdk::{
-    contractclient, Address, Env,
+    contractclient, Address, Env, Vec,
 };
 use soroban_token_sdk::metadata::TokenMetadata;
 
@@ -13,12 +13,19 @@ pub trait LPTokenInterface {
     fn borrow(env: Env, to: Address, amount: i128);
     fn repay(env: Env, from: Address, amount: i128);
     fn repay_with_burn(env: Env, from: Address, amount: i128, fee: i128);
+    fn repay_with_burn_batch(env: Env, burns: Vec<(Address, i128, i128)>);
+    fn redeem_to_repay(env: Env, from: Address, amount: i128, fee: i128, max_shares: u128) -> u128;
+    fn slash(env: Env, from: Address, amount: i128) -> u128;
     fn get_total_assets(env: Env) -> i128;
     fn get_accumulated_yield(env: Env) -> i128;
     fn get_share_value(env: Env) -> i128;
     fn balance(env: Env, user: Address) -> i128;
     fn total_underlying(env: Env) -> i128;
     fn update_index(env: Env);
+    fn distribute_yield(env: Env, from: Address, amount: i128);
+    fn lock_collateral(env: Env, user: Address, amount: i128);
+    fn unlock_collateral(env: Env, user: Address, amount: i128);
+    fn available_balance(env: Env, user: Address) -> i128;
 
     fn deposit(env: Env, from: Address, amount: i128) -> i128;
     fn withdraw(env: Env, from: Address, lp_amount: i128) -> i128;
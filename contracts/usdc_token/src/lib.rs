@@ -365,7 +365,7 @@ mod test {
         let name = String::from_str(&env, "Testnet USDC");
         let symbol = String::from_str(&env, "USDC");
         
-        client.initialize(&admin, &name, &symbol, &7, &10000000_0000000);
+        client.initialize(&admin, &name, &symbol, &7, &100_000_000_000_000);
         
         assert_eq!(client.name(), name);
         assert_eq!(client.symbol(), symbol);